@@ -1,15 +1,81 @@
-use crate::backend::{Backend, PassCliBackend, PassStatusError};
-use crate::store::{build_store_index, path_to_store_key, EntryKind, StoreEntry};
+use crate::audit::AuditLog;
+use crate::backend::{
+    default_store_dir, last_line, move_destination, store_env_for, Backend, PassCliBackend,
+    PassStatusError,
+};
+use crate::browser;
+use crate::clipboard;
+use crate::config::{expand_tilde, Config, Profile};
+use crate::diagnostics;
+use crate::fields;
+use crate::gpg;
+use crate::keymap::Keymap;
+use crate::session::Session;
+use crate::store::{
+    build_store_index, case_collisions, format_age, is_empty_dir, path_to_store_key,
+    prune_empty_dirs, EntryKind, StoreEntry,
+};
+use crate::strength::{self, Strength};
+use crate::theme::{Glyphs, Theme};
+use crate::watch::StoreWatcher;
 use anyhow::Result;
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use zeroize::Zeroize;
 
 #[derive(Debug, Clone)]
 pub enum ModalAction {
     AddHere,
+    InsertPath,
+    TemplatePath,
+    Insert {
+        entry: String,
+    },
+    EditLine {
+        entry: String,
+    },
+    /// Adding named an entry that exists; confirming edits it instead
+    EditExisting {
+        entry: String,
+    },
     DeleteSelected,
-    Rename { from: String },
+    DeleteMarked {
+        keys: Vec<String>,
+    },
+    Rename {
+        from: String,
+    },
+    /// Text to look for in entry names; next asks for its replacement
+    BulkFind,
+    BulkReplace {
+        find: String,
+    },
+    BulkRename {
+        moves: Vec<(String, String)>,
+    },
+    Copy {
+        from: String,
+    },
+    MoveMarked {
+        keys: Vec<String>,
+    },
+    PruneEmpty {
+        dir: String,
+    },
+    Regenerate {
+        entry: String,
+    },
+    SwitchProfile,
+    SwitchStore,
+    Grep,
+    YankField {
+        entry: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -18,6 +84,8 @@ pub enum Modal {
         title: String,
         buffer: String,
         action: ModalAction,
+        /// Why the last submission was rejected, shown under the buffer
+        error: Option<String>,
     },
     Confirm {
         title: String,
@@ -25,47 +93,245 @@ pub enum Modal {
         action: ModalAction,
         selected_ok: bool,
     },
+    Info {
+        title: String,
+        lines: Vec<String>,
+    },
+    /// Free text over several lines; Enter starts a new line
+    Multiline {
+        title: String,
+        buffer: String,
+        action: ModalAction,
+    },
+    Flash {
+        entry: String,
+        secret: String,
+    },
+    Picker {
+        title: String,
+        items: Vec<String>,
+        selected: usize,
+        action: ModalAction,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub enum PendingAction {
     Edit(String),
     Add(String),
+    Insert {
+        entry: String,
+        content: String,
+    },
+    /// Replace the first line of an entry, keeping the rest
+    EditLine {
+        entry: String,
+        line: String,
+    },
     Delete,
+    Rename {
+        from: String,
+        to: String,
+    },
+    Copy {
+        from: String,
+        to: String,
+    },
+    DeleteMany(Vec<String>),
+    /// `(from, to)` pairs, checked for clashes before any entry is moved
+    MoveMany(Vec<(String, String)>),
+    Prune(String),
+    Pager(String),
+    Regenerate(String),
+    Undo,
+    SwitchProfile(String),
+    SwitchStore(PathBuf),
+    Grep(String),
+    YankField {
+        entry: String,
+        field: String,
+    },
+    GitPull,
+    GitPush,
+}
+
+/// A completed operation that `u` can reverse.
+#[derive(Debug, Clone)]
+pub enum UndoOp {
     Rename { from: String, to: String },
 }
 
+impl PendingAction {
+    /// Whether the action writes to the store (and so should hold the store lock).
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            PendingAction::Edit(_)
+                | PendingAction::Add(_)
+                | PendingAction::Insert { .. }
+                | PendingAction::EditLine { .. }
+                | PendingAction::Delete
+                | PendingAction::Rename { .. }
+                | PendingAction::Copy { .. }
+                | PendingAction::DeleteMany(_)
+                | PendingAction::MoveMany(_)
+                | PendingAction::Prune(_)
+                | PendingAction::Regenerate(_)
+                | PendingAction::Undo
+                | PendingAction::GitPull
+        )
+    }
+
+    /// Whether `--read-only` refuses this action: anything that changes the store or its remote.
+    pub fn is_write(&self) -> bool {
+        self.is_mutating() || matches!(self, PendingAction::GitPush)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PreviewMode {
     Raw,
+    /// Password and `key: value` lines laid out as a table
+    Fields,
     Qr,
+    Otp,
+}
+
+/// Order of entries within a directory; directories always come before entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    Name,
+    /// Most recently modified first
+    Recent,
+}
+
+/// Status messages kept for the message log (`m`)
+const MESSAGE_LOG_LEN: usize = 50;
+/// Moves (and skips) listed by name before a bulk rename
+const BULK_PREVIEW_LEN: usize = 8;
+/// Filters kept for Up/Down recall in the filter prompt
+const FILTER_HISTORY_LEN: usize = 20;
+/// Commits listed in an entry's history (`L`)
+const GIT_LOG_LEN: usize = 20;
+/// Shown in the add and insert boxes when pass would have nobody to encrypt the entry to
+const NO_GPG_ID: &str = "No .gpg-id for this path; run `pass init` first";
+
+/// Bounds of the list's share of the width, in percent
+const LIST_WIDTH_RANGE: (u16, u16) = (20, 80);
+
+/// Typing pause after which jump mode ends
+const JUMP_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// A decrypted preview coming back from a worker thread, tagged with the request it answers
+pub struct PreviewResult {
+    id: u64,
+    rel: String,
+    mode: PreviewMode,
+    allow_unlock: bool,
+    /// Read from a plain file rather than decrypted, so nothing needs masking
+    plain: bool,
+    result: Result<String>,
 }
 
 type EntryIndex = usize;
 type DirKey = String;
 
 pub struct App {
-    pub backend: Box<dyn Backend>,
+    pub backend: Arc<dyn Backend>,
+    pub config: Config,
+    pub profile: Option<String>,
+    pub theme: Theme,
+    pub glyphs: &'static Glyphs,
+    pub keymap: Keymap,
+    pub audit: Option<AuditLog>,
     pub store_dir: PathBuf,
+    /// Stores given with repeated `--store`, switched between with the store picker
+    pub stores: Vec<PathBuf>,
     pub cwd: PathBuf,
     pub entries: Vec<StoreEntry>,
     pub rows: Vec<ViewRow>,
     pub expanded: HashSet<DirKey>,
+    pub sort_mode: SortMode,
     pub cursor: usize,
+    /// Rows visible in the list pane at the last draw, for half-page movement
+    pub list_height: u16,
     pub quit: bool,
+    /// Set by `--read-only`; actions that change the store are refused
+    pub read_only: bool,
+    /// Set by `--print-selected`; Enter on an entry quits, leaving its key in `picked`
+    pub print_selected: bool,
+    pub picked: Option<String>,
     pub modal: Option<Modal>,
+    pub flash_deadline: Option<Instant>,
+    /// Password shown in the status line by peek, wiped at `peek_deadline`
+    pub peek: Option<String>,
+    pub peek_deadline: Option<Instant>,
+    /// When the clipboard holding a yanked secret is cleared, for the status countdown
+    pub clipboard_expires_at: Option<Instant>,
+    pub clipboard_note: String,
+    /// Entry whose `copy_sequence` is under way and the step the next press copies
+    pub copy_step: Option<(String, usize)>,
     pub pending: Option<PendingAction>,
     pub pending_preview: Option<(String, PreviewMode)>,
 
     pub filter: String,
     pub filter_mode: bool,
     pub filter_input: String,
+    /// Submitted filters, oldest first, for Up/Down in the filter prompt
+    pub filter_history: Vec<String>,
+    /// Position in `filter_history` while recalling, and the input typed before recalling began
+    filter_recall: Option<(usize, String)>,
+    /// Prefix typed in jump mode (`f`); `Some` while jump mode is active
+    pub jump_prefix: Option<String>,
+    pub jump_deadline: Option<Instant>,
+    /// Row number typed after `:`; `Some` while the goto prompt is open
+    pub goto_input: Option<String>,
+    /// Pattern of the last `pass grep` and the entries it matched; limits the list while set
+    pub grep: Option<(String, HashSet<String>)>,
 
     pub status: Option<String>,
+    /// Running while `watch_store` is on; replaced when the store changes
+    watcher: Option<StoreWatcher>,
+    /// Recent status messages, oldest first, capped at `MESSAGE_LOG_LEN`
+    pub messages: Vec<(Instant, String)>,
+    /// Reversible operations performed this session, most recent last
+    pub undo_stack: Vec<UndoOp>,
+    /// Rows marked with Space; delete and rename act on all of them while any are marked
+    pub selection: HashSet<EntryIndex>,
+    /// List every entry by its full path instead of the tree
+    pub flat: bool,
+    /// The store is a git repository with uncommitted changes, checked on every refresh
+    pub git_dirty: bool,
     pub preview_key: Option<String>,
     pub preview_text: String,
     pub preview_is_error: bool,
+    /// `preview_text` was cut at `preview_max_kb`, so it is not the whole entry
+    preview_truncated: bool,
     pub preview_mode: PreviewMode,
+    /// Share of the width given to the list in percent; unset follows `preview_width` or 50/50
+    pub list_width: Option<u16>,
+    /// The width chosen with `<` and `>`, this run or an earlier one, kept in the session file
+    saved_list_width: Option<u16>,
+    /// Rating of the previewed password, when the entry starts with one
+    pub preview_strength: Option<Strength>,
+    /// How decrypted entries are shown: `Raw` or `Fields`, toggled with Tab
+    pub text_mode: PreviewMode,
+    /// Lines scrolled past at the top of the preview pane
+    pub preview_scroll: u16,
+    /// Columns panned past at the left of the preview pane; only used while wrapping is off
+    pub preview_hscroll: u16,
+    /// Wrap long preview lines; when off they are cut at the pane edge and can be panned
+    pub preview_wrap: bool,
+    /// Hide values in the preview until revealed; re-armed whenever another entry is shown
+    pub preview_masked: bool,
+    /// Request id of the preview still being decrypted; results for any other id are stale
+    pub preview_loading: Option<u64>,
+    /// When the cursor came to rest on `preview_pending_key`, for `preview_delay_ms`
+    pub preview_pending_since: Option<Instant>,
+    preview_pending_key: Option<String>,
+    preview_requests: u64,
+    preview_tx: Sender<PreviewResult>,
+    preview_rx: Receiver<PreviewResult>,
 }
 
 #[derive(Debug, Clone)]
@@ -75,63 +341,560 @@ pub struct ViewRow {
 }
 
 impl App {
-    pub fn new_with_store(store_dir: Option<PathBuf>) -> Result<Self> {
-        let store_dir = store_dir.unwrap_or_else(password_store_dir);
-        if !store_dir.exists() {
-            anyhow::bail!(
-                "Password store not found: {}. Set PASSWORD_STORE_DIR or --store.",
-                store_dir.display()
-            );
-        }
-        let entries = build_store_index(&store_dir)?;
+    /// Run the `pass-tui doctor` checks against the store the TUI would open.
+    pub fn doctor(
+        store_dir: Option<PathBuf>,
+        profile: Option<&str>,
+        config: &Config,
+    ) -> Result<Vec<diagnostics::Check>> {
+        let settings = match profile {
+            Some(name) => config.profile(name)?.clone(),
+            None => Profile::default(),
+        };
+        let store_dir = store_dir
+            .or_else(|| settings.store.as_deref().map(expand_tilde))
+            .unwrap_or_else(|| password_store_dir(&settings, config));
+        let store_dir = store_dir.canonicalize().unwrap_or(store_dir);
+        let backend = profile_backend(store_dir.clone(), &settings, config);
+        Ok(diagnostics::doctor(&store_dir, backend.as_ref()))
+    }
+
+    pub fn new_with_store(store_dir: Option<PathBuf>, config: Config) -> Result<Self> {
+        Self::new_with_profile(store_dir, None, config)
+    }
+
+    /// Start with the named profile; an explicit `store_dir` still wins over the profile's.
+    pub fn new_with_profile(
+        store_dir: Option<PathBuf>,
+        profile: Option<&str>,
+        config: Config,
+    ) -> Result<Self> {
+        let settings = match profile {
+            Some(name) => config.profile(name)?.clone(),
+            None => Profile::default(),
+        };
+        let store_dir = store_dir
+            .or_else(|| settings.store.as_deref().map(expand_tilde))
+            .unwrap_or_else(|| password_store_dir(&settings, &config));
+        let (canonical_dir, entries) = open_store(&store_dir, config.index_plain)?;
         let mut expanded = HashSet::new();
         expanded.insert(String::new()); // root expanded by default
+        let list_width = config.list_width_percent.map(clamp_list_width);
+
+        let (audit, audit_error) = if config.audit_log {
+            match AuditLog::open_default() {
+                Ok(log) => (Some(log), None),
+                Err(err) => (None, Some(format!("Audit log disabled: {}", err))),
+            }
+        } else {
+            (None, None)
+        };
+        let (keymap, keymap_error) = match Keymap::from_config(&config.keys) {
+            Ok(keymap) => (keymap, None),
+            Err(err) => (Keymap::default(), Some(format!("{err:#}"))),
+        };
 
-        Ok(Self {
-            backend: Box::new(PassCliBackend::new(Some(store_dir.clone()))),
-            store_dir,
+        let (preview_tx, preview_rx) = mpsc::channel();
+
+        let backend = profile_backend(canonical_dir.clone(), &settings, &config);
+        let backend_error = backend.check().err().map(|err| err.to_string());
+
+        let mut app = Self {
+            backend,
+            audit,
+            theme: config.theme_for(&settings),
+            glyphs: Glyphs::select(config.ascii),
+            keymap,
+            config,
+            profile: profile.map(str::to_string),
+            stores: vec![store_dir.clone()],
+            store_dir: canonical_dir,
             cwd: PathBuf::new(),
             entries,
             rows: Vec::new(),
             expanded,
+            sort_mode: SortMode::Name,
             cursor: 0,
+            list_height: 0,
             quit: false,
+            read_only: false,
+            print_selected: false,
+            picked: None,
             modal: None,
+            flash_deadline: None,
+            peek: None,
+            peek_deadline: None,
+            clipboard_expires_at: None,
+            clipboard_note: String::new(),
+            copy_step: None,
             pending: None,
             pending_preview: None,
             filter: String::new(),
             filter_mode: false,
             filter_input: String::new(),
+            filter_history: Vec::new(),
+            filter_recall: None,
+            jump_prefix: None,
+            goto_input: None,
+            jump_deadline: None,
+            grep: None,
             status: None,
+            messages: Vec::new(),
+            watcher: None,
+            undo_stack: Vec::new(),
+            selection: HashSet::new(),
+            flat: false,
+            git_dirty: false,
             preview_key: None,
             preview_text: String::new(),
             preview_is_error: false,
+            preview_truncated: false,
             preview_mode: PreviewMode::Raw,
-        })
+            preview_strength: None,
+            list_width,
+            saved_list_width: None,
+            text_mode: PreviewMode::Raw,
+            preview_scroll: 0,
+            preview_hscroll: 0,
+            preview_wrap: true,
+            preview_masked: true,
+            preview_loading: None,
+            preview_pending_since: None,
+            preview_pending_key: None,
+            preview_requests: 0,
+            preview_tx,
+            preview_rx,
+        };
+        // one status for everything found at startup, so no warning hides another
+        let notices: Vec<String> = [
+            backend_error,
+            keymap_error,
+            audit_error,
+            app.case_collision_warning(),
+            app.key_expiry_warning(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !notices.is_empty() {
+            app.set_status(notices.join(" | "));
+        }
+        app.watch_store();
+        app.check_git_dirty();
+        if let Some(cwd) = &settings.cwd {
+            app.set_cwd(cwd);
+        }
+        Ok(app)
+    }
+
+    /// A warning listing entries whose names differ only by case, which a case-insensitive
+    /// filesystem (macOS, Windows) cannot keep apart. Shown once at startup.
+    fn case_collision_warning(&self) -> Option<String> {
+        let groups = case_collisions(&self.entries);
+        if groups.is_empty() {
+            return None;
+        }
+        let listed: Vec<String> = groups.iter().map(|keys| keys.join(" / ")).collect();
+        Some(format!(
+            "Entries differ only by case: {}",
+            listed.join("; ")
+        ))
+    }
+
+    /// A warning about gpg keys of the store that expire within `key_expiry_warn_days`.
+    /// Shown once at startup; a missing gpg is left to `pass-tui doctor` to report.
+    fn key_expiry_warning(&self) -> Option<String> {
+        if self.config.key_expiry_warn_days == 0 {
+            return None;
+        }
+        let keys = gpg::list_keys(&gpg::store_recipients(&self.store_dir)).ok()?;
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        gpg::expiry_warning(&keys, now, self.config.key_expiry_warn_days)
+    }
+
+    /// Replace the store, backend and theme with those of a configured profile.
+    pub fn switch_profile(&mut self, name: &str) -> Result<()> {
+        let settings = self.config.profile(name)?.clone();
+        let store_dir = settings
+            .store
+            .as_deref()
+            .map(expand_tilde)
+            .unwrap_or_else(|| password_store_dir(&settings, &self.config));
+        let (canonical_dir, entries) = open_store(&store_dir, self.config.index_plain)?;
+
+        self.backend = profile_backend(canonical_dir.clone(), &settings, &self.config);
+        self.theme = self.config.theme_for(&settings);
+        self.profile = Some(name.to_string());
+        self.stores = vec![store_dir];
+        self.reset_to_store(canonical_dir, entries);
+        self.set_status(format!("Switched to profile '{}'", name));
+        if let Some(cwd) = &settings.cwd {
+            self.set_cwd(cwd);
+        }
+        self.apply_filter();
+        Ok(())
+    }
+
+    /// Bring back the list width chosen when pass-tui last quit and, if `persist_filter` is on,
+    /// the filter.
+    pub fn restore_session(&mut self) {
+        match Session::load() {
+            Ok(session) => {
+                self.saved_list_width = session.list_width.map(clamp_list_width);
+                self.list_width = self.saved_list_width.or(self.list_width);
+                if self.config.persist_filter_history {
+                    self.filter_history = session.filter_history;
+                }
+                if self.config.persist_filter {
+                    self.filter = session.filter;
+                    self.apply_filter();
+                }
+            }
+            Err(err) => self.set_status(format!("Could not restore the session: {:#}", err)),
+        }
+    }
+
+    /// Save the session for the next run. With `persist_filter` or `persist_filter_history` off
+    /// those are left out, and the file is removed when nothing else needs keeping.
+    pub fn save_session(&self) -> Result<()> {
+        let filter = if self.config.persist_filter {
+            self.filter.clone()
+        } else {
+            String::new()
+        };
+        let filter_history = if self.config.persist_filter_history {
+            self.filter_history.clone()
+        } else {
+            Vec::new()
+        };
+        if filter.is_empty() && filter_history.is_empty() && self.saved_list_width.is_none() {
+            return match Session::path() {
+                Some(path) => Session::remove(&path),
+                None => Ok(()),
+            };
+        }
+        Session {
+            filter,
+            list_width: self.saved_list_width,
+            filter_history,
+        }
+        .save()
+    }
+
+    /// Open the filter prompt with the current filter.
+    pub fn start_filter(&mut self) {
+        self.filter_mode = true;
+        self.filter_input = self.filter.clone();
+        self.filter_recall = None;
+    }
+
+    /// Apply the filter typed in the prompt and remember it for recall; a repeat moves to the
+    /// end of the history instead of being kept twice.
+    pub fn submit_filter(&mut self) {
+        self.filter = self.filter_input.clone();
+        self.filter_mode = false;
+        self.filter_recall = None;
+        if !self.filter.is_empty() {
+            self.filter_history.retain(|old| *old != self.filter);
+            self.filter_history.push(self.filter.clone());
+            let excess = self.filter_history.len().saturating_sub(FILTER_HISTORY_LEN);
+            self.filter_history.drain(..excess);
+        }
+        self.apply_filter();
+    }
+
+    /// Put an older (or newer) remembered filter in the prompt. Going past the newest brings
+    /// back what was typed before recalling began.
+    pub fn recall_filter(&mut self, older: bool) {
+        let len = self.filter_history.len();
+        let pos = match (&self.filter_recall, older) {
+            (None, true) if len > 0 => len - 1,
+            (None, _) => return,
+            (Some((pos, _)), true) => pos.saturating_sub(1),
+            (Some((pos, _)), false) if pos + 1 < len => pos + 1,
+            (Some(_), false) => {
+                if let Some((_, draft)) = self.filter_recall.take() {
+                    self.filter_input = draft;
+                }
+                return;
+            }
+        };
+        let draft = match self.filter_recall.take() {
+            Some((_, draft)) => draft,
+            None => self.filter_input.clone(),
+        };
+        self.filter_input = self.filter_history[pos].clone();
+        self.filter_recall = Some((pos, draft));
+    }
+
+    /// Move the divider between list and preview by `delta` percentage points of the width.
+    pub fn resize_list(&mut self, delta: i16) {
+        let current = self.list_width.unwrap_or(50) as i16;
+        let width = clamp_list_width(current.saturating_add(delta).max(0) as u16);
+        self.list_width = Some(width);
+        self.saved_list_width = Some(width);
+        self.set_status(format!("List takes {}% of the width", width));
+    }
+
+    /// Scope the list to a directory of the store, falling back to the root if it is unknown.
+    pub fn set_cwd(&mut self, dir: &Path) {
+        let exists = self
+            .entries
+            .iter()
+            .any(|e| e.is_dir() && e.path.as_path() == dir);
+        if exists {
+            self.cwd = dir.to_path_buf();
+        } else {
+            self.cwd = PathBuf::new();
+            self.set_status(format!(
+                "Directory '{}' not found in store; showing root",
+                dir.display()
+            ));
+        }
+        self.cursor = 0;
+    }
+
+    /// Switch to another of the configured stores, keeping the active profile's settings.
+    pub fn switch_store(&mut self, store_dir: &Path) -> Result<()> {
+        let settings = match &self.profile {
+            Some(name) => self.config.profile(name)?.clone(),
+            None => Profile::default(),
+        };
+        let (canonical_dir, entries) = open_store(store_dir, self.config.index_plain)?;
+        self.backend = profile_backend(canonical_dir.clone(), &settings, &self.config);
+        self.reset_to_store(canonical_dir, entries);
+        self.set_status(format!("Switched to store {}", store_dir.display()));
+        self.apply_filter();
+        Ok(())
+    }
+
+    /// Show a freshly indexed store from its root with nothing filtered or previewed.
+    fn reset_to_store(&mut self, canonical_dir: PathBuf, entries: Vec<StoreEntry>) {
+        self.close_modal();
+        self.store_dir = canonical_dir;
+        self.entries = entries;
+        self.cwd = PathBuf::new();
+        self.expanded.clear();
+        self.expanded.insert(String::new());
+        self.cursor = 0;
+        self.filter.clear();
+        self.filter_input.clear();
+        self.filter_mode = false;
+        self.grep = None;
+        self.selection.clear();
+        self.pending_preview = None;
+        self.preview_key = None;
+        self.preview_text.clear();
+        self.preview_is_error = false;
+        self.preview_scroll = 0;
+        self.preview_hscroll = 0;
+        self.watch_store();
+        self.check_git_dirty();
+    }
+
+    /// Ask the backend whether the store has uncommitted changes; stores that are not git
+    /// repositories, or that git cannot read, never show the marker.
+    pub fn check_git_dirty(&mut self) {
+        self.git_dirty = self.backend.git_dirty().unwrap_or(false);
+    }
+
+    /// (Re)start watching the current store for outside changes, if enabled.
+    fn watch_store(&mut self) {
+        self.watcher = self.config.watch_store.then(|| {
+            let interval = Duration::from_millis(self.config.watch_interval_ms);
+            StoreWatcher::spawn(self.store_dir.clone(), interval)
+        });
+    }
+
+    /// Whether a watcher is looking for outside changes to the store.
+    pub fn watching_store(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Re-index if the watcher saw entries change. Returns whether the list was refreshed.
+    pub fn poll_store_changes(&mut self) -> bool {
+        if !self.watcher.as_ref().is_some_and(StoreWatcher::changed) {
+            return false;
+        }
+        if let Err(err) = self.refresh() {
+            self.set_status(err.to_string());
+        }
+        true
+    }
+
+    /// Display name of the active store, used in the header when several are configured.
+    pub fn store_name(&self) -> String {
+        self.store_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.store_dir.display().to_string())
+    }
+
+    pub fn open_store_modal(&mut self) {
+        if self.stores.len() < 2 {
+            self.set_status("Only one store configured; pass --store more than once");
+            return;
+        }
+        let items: Vec<String> = self
+            .stores
+            .iter()
+            .map(|store| store.display().to_string())
+            .collect();
+        let selected = self
+            .stores
+            .iter()
+            .position(|store| store.canonicalize().ok().as_ref() == Some(&self.store_dir))
+            .unwrap_or(0);
+        self.modal = Some(Modal::Picker {
+            title: "Switch store".into(),
+            items,
+            selected,
+            action: ModalAction::SwitchStore,
+        });
+    }
+
+    pub fn open_profile_modal(&mut self) {
+        let items: Vec<String> = self.config.profiles.keys().cloned().collect();
+        if items.is_empty() {
+            self.set_status("No profiles configured");
+            return;
+        }
+        let selected = self
+            .profile
+            .as_ref()
+            .and_then(|current| items.iter().position(|name| name == current))
+            .unwrap_or(0);
+        self.modal = Some(Modal::Picker {
+            title: "Switch profile".into(),
+            items,
+            selected,
+            action: ModalAction::SwitchProfile,
+        });
     }
 
+    /// Re-index the store, keeping the cursor on the same entry when it still exists.
     pub fn refresh(&mut self) -> Result<()> {
-        self.entries = build_store_index(&self.store_dir)?;
+        self.check_git_dirty();
+        let selected = self.selected_store_key();
+        let marked: HashSet<String> = self
+            .selection
+            .iter()
+            .map(|&idx| self.entries[idx].store_key())
+            .collect();
+        self.entries = build_store_index(&self.store_dir, self.config.index_plain)?;
+        // the index is current, so whatever the watcher saw (such as our own writes) is covered
+        if let Some(watcher) = &self.watcher {
+            watcher.resync();
+        }
+        // indices change with the new index; keep marks on entries that still exist
+        self.selection = (0..self.entries.len())
+            .filter(|&idx| marked.contains(&self.entries[idx].store_key()))
+            .collect();
+        if !self
+            .entries
+            .iter()
+            .any(|e| e.is_dir() && e.path == self.cwd)
+        {
+            // the directory being shown was removed
+            self.cwd = PathBuf::new();
+        }
+        // apply_filter clamps the cursor, which is where it stays if the entry is gone
         self.apply_filter();
+        if let Some(selected) = selected {
+            self.select_key(&selected);
+        }
         Ok(())
     }
 
+    /// Put the cursor on the row for `key`, expanding the directories above it so it is listed.
+    /// Returns false (leaving the cursor alone) when no such row can be shown.
+    pub fn select_key(&mut self, key: &str) -> bool {
+        let Some(idx) = self.entries.iter().position(|e| e.store_key() == key) else {
+            return false;
+        };
+        let mut parent = self.entries[idx].path.parent();
+        let mut expanded_any = false;
+        while let Some(dir) = parent {
+            if dir.starts_with(&self.cwd) && dir != self.cwd {
+                expanded_any |= self
+                    .expanded
+                    .insert(path_to_store_key(self.relative_to_cwd(dir)));
+            }
+            parent = dir.parent();
+        }
+        if expanded_any {
+            self.apply_filter();
+        }
+        match self.rows.iter().position(|row| row.idx == idx) {
+            Some(pos) => {
+                self.cursor = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn apply_filter(&mut self) {
-        let filter_active = !self.filter.is_empty();
+        let filter_active = !self.filter.is_empty() || self.grep.is_some();
         let mut include: HashSet<EntryIndex> = HashSet::new();
         let mut index_by_path: HashMap<PathBuf, EntryIndex> = HashMap::new();
+        // best score of an entry or anything beneath it, used to order siblings
+        let mut scores: HashMap<EntryIndex, i64> = HashMap::new();
 
         for (idx, entry) in self.entries.iter().enumerate() {
             index_by_path.insert(entry.path.clone(), idx);
             if !entry.path.starts_with(&self.cwd) || entry.path == self.cwd {
                 continue;
             }
-            if filter_active && !entry.display_name().contains(&self.filter) {
-                continue;
+            if let Some((_, matches)) = &self.grep {
+                if entry.is_dir() || !matches.contains(&entry.store_key()) {
+                    continue;
+                }
             }
-            include.insert(idx);
             if filter_active {
-                self.add_visible_ancestors(idx, &mut include, &index_by_path);
+                // a filter with a slash is a path, e.g. "work/aws"; otherwise match the name only
+                let target = if self.filter.contains('/') {
+                    entry.store_key()
+                } else {
+                    entry.display_name()
+                };
+                let Some((score, _)) = fuzzy_match(&target, &self.filter) else {
+                    continue;
+                };
+                scores.insert(idx, score);
+            }
+            include.insert(idx);
+        }
+        if self.flat {
+            // every matching entry on its own row, without directories or expansion state
+            let mut flat: Vec<EntryIndex> = include
+                .into_iter()
+                .filter(|&idx| !self.entries[idx].is_dir())
+                .collect();
+            flat.sort_by(|&left, &right| {
+                let by_score = scores
+                    .get(&right)
+                    .unwrap_or(&0)
+                    .cmp(scores.get(&left).unwrap_or(&0));
+                by_score.then_with(|| self.cmp_entries(left, right))
+            });
+            self.rows = flat
+                .into_iter()
+                .map(|idx| ViewRow {
+                    idx,
+                    branches: Vec::new(),
+                })
+                .collect();
+            self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+            return;
+        }
+        if filter_active {
+            let matched: Vec<(EntryIndex, i64)> = scores.iter().map(|(&i, &s)| (i, s)).collect();
+            for (idx, score) in matched {
+                self.add_visible_ancestors(idx, score, &mut include, &mut scores, &index_by_path);
             }
         }
 
@@ -147,7 +910,13 @@ impl App {
         }
 
         for siblings in children.values_mut() {
-            siblings.sort_by(|&left, &right| self.cmp_entries(left, right));
+            siblings.sort_by(|&left, &right| {
+                let by_score = scores
+                    .get(&right)
+                    .unwrap_or(&0)
+                    .cmp(scores.get(&left).unwrap_or(&0));
+                by_score.then_with(|| self.cmp_entries(left, right))
+            });
         }
 
         self.rows.clear();
@@ -162,7 +931,9 @@ impl App {
     fn add_visible_ancestors(
         &self,
         idx: EntryIndex,
+        score: i64,
         include: &mut HashSet<EntryIndex>,
+        scores: &mut HashMap<EntryIndex, i64>,
         index_by_path: &HashMap<PathBuf, EntryIndex>,
     ) {
         let mut current = self.entries[idx].path.as_path();
@@ -172,6 +943,8 @@ impl App {
             }
             if let Some(&parent_idx) = index_by_path.get(parent) {
                 include.insert(parent_idx);
+                let best = scores.entry(parent_idx).or_insert(score);
+                *best = (*best).max(score);
             }
             current = parent;
         }
@@ -191,13 +964,31 @@ impl App {
 
         let left_entry = &self.entries[left];
         let right_entry = &self.entries[right];
-        match (left_entry.kind, right_entry.kind) {
-            (EntryKind::Dir, EntryKind::Entry) => Ordering::Less,
-            (EntryKind::Entry, EntryKind::Dir) => Ordering::Greater,
+        match (left_entry.is_dir(), right_entry.is_dir()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // entries without a time sort last
+            _ if self.sort_mode == SortMode::Recent => right_entry
+                .modified
+                .cmp(&left_entry.modified)
+                .then_with(|| left_entry.path.cmp(&right_entry.path)),
             _ => left_entry.path.cmp(&right_entry.path),
         }
     }
 
+    /// Switch between name order and most recently modified first.
+    pub fn cycle_sort(&mut self) {
+        self.sort_mode = match self.sort_mode {
+            SortMode::Name => SortMode::Recent,
+            SortMode::Recent => SortMode::Name,
+        };
+        self.set_status(match self.sort_mode {
+            SortMode::Name => "Sorted by name".to_string(),
+            SortMode::Recent => "Sorted by most recently modified".to_string(),
+        });
+        self.apply_filter();
+    }
+
     fn build_rows(
         &mut self,
         children: &BTreeMap<DirKey, Vec<EntryIndex>>,
@@ -226,164 +1017,1562 @@ impl App {
         }
     }
 
-    pub fn enter(&mut self) {
-        if let Some(row) = self.rows.get(self.cursor) {
-            let entry = &self.entries[row.idx];
-            if entry.is_dir() {
-                let key = self.entry_key(row.idx);
-                if self.expanded.contains(&key) {
-                    self.expanded.remove(&key);
-                } else {
-                    self.expanded.insert(key);
-                }
-                self.apply_filter();
-            }
+    /// Switch between the tree and the flat list, staying on the selected entry.
+    pub fn toggle_flat(&mut self) {
+        let selected = self.selected_store_key();
+        self.flat = !self.flat;
+        self.apply_filter();
+        if let Some(key) = selected {
+            self.select_key(&key);
         }
     }
 
-    pub fn selected_entry_path(&self) -> Option<String> {
-        self.rows
-            .get(self.cursor)
-            .and_then(|r| self.entries[r.idx].relative_entry_path())
+    /// Move the cursor to the next row, wrapping to the first with `wrap_around`. Returns
+    /// whether it moved.
+    pub fn move_down(&mut self) -> bool {
+        if self.cursor + 1 < self.rows.len() {
+            self.cursor += 1;
+        } else if self.config.wrap_around && self.rows.len() > 1 {
+            self.cursor = 0;
+        } else {
+            return false;
+        }
+        true
     }
 
-    pub fn delete_selected(&mut self) -> Result<()> {
-        if let Some(row) = self.rows.get(self.cursor) {
-            let entry = &self.entries[row.idx];
-            if entry.is_dir() {
-                let rel = entry.store_key();
-                self.backend.rm(&rel, true)?;
-            } else if let Some(rel) = entry.relative_entry_path() {
-                self.backend.rm(&rel, false)?;
-            }
-            self.refresh()?;
+    /// Move the cursor to the previous row, wrapping to the last with `wrap_around`. Returns
+    /// whether it moved.
+    pub fn move_up(&mut self) -> bool {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+        } else if self.config.wrap_around && self.rows.len() > 1 {
+            self.cursor = self.rows.len() - 1;
+        } else {
+            return false;
         }
-        Ok(())
+        true
     }
 
-    pub fn open_add_modal(&mut self) {
-        // Prefill with absolute path (within store). If hovering a directory, prefill "dir/".
-        let mut prefix = String::new();
-        if let Some(row) = self.rows.get(self.cursor) {
-            let entry = &self.entries[row.idx];
-            if entry.is_dir() {
-                prefix = entry.store_key();
-            } else if let Some(parent) = entry.path.parent() {
-                prefix = path_to_store_key(parent);
-            }
-            if !prefix.is_empty() {
-                prefix.push('/');
-            }
-        }
-        self.modal = Some(Modal::Input {
-            title: "New entry path".into(),
-            buffer: prefix,
-            action: ModalAction::AddHere,
-        });
+    /// Replace the filter and rebuild the list, as if it had been typed after `/`.
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        self.apply_filter();
     }
 
-    pub fn open_rename_modal(&mut self) {
-        if let Some((from, suggested)) = self.selected_any_path_and_name() {
-            self.modal = Some(Modal::Input {
-                title: "Rename entry".into(),
-                buffer: suggested,
-                action: ModalAction::Rename { from },
-            });
+    /// Expand the selected directory, or collapse it if it is expanded. Returns false when the
+    /// selected row is not a directory.
+    pub fn toggle_expand(&mut self) -> bool {
+        let Some(row) = self.rows.get(self.cursor) else {
+            return false;
+        };
+        if !self.entries[row.idx].is_dir() {
+            return false;
         }
+        let key = self.entry_key(row.idx);
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+        self.apply_filter();
+        true
     }
 
-    pub fn open_delete_modal(&mut self) {
-        self.modal = Some(Modal::Confirm {
-            title: "Confirm Delete".into(),
-            message: "Delete selected entry?".into(),
-            action: ModalAction::DeleteSelected,
-            selected_ok: true,
-        });
+    /// Move the cursor by `delta` rows, stopping at either end of the list.
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.rows.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
     }
 
-    pub fn submit_modal(&mut self) -> Option<PendingAction> {
-        let modal = self.modal.take()?;
-        match modal {
-            Modal::Input { action, buffer, .. } => match action {
-                ModalAction::AddHere => {
-                    let name = buffer.trim();
-                    if name.is_empty() {
-                        None
-                    } else {
-                        Some(PendingAction::Add(name.to_string()))
-                    }
-                }
-                ModalAction::DeleteSelected => None,
-                ModalAction::Rename { from } => {
-                    let to = buffer.trim();
-                    if to.is_empty() || to == from {
-                        return None;
-                    }
-                    if self.path_exists(to) {
-                        self.status = Some(format!("Target '{}' exists — rename aborted", to));
-                        None
-                    } else {
-                        Some(PendingAction::Rename {
-                            from,
-                            to: to.to_string(),
-                        })
-                    }
-                }
-            },
-            Modal::Confirm {
-                action,
-                selected_ok,
-                ..
-            } => match action {
-                ModalAction::DeleteSelected if selected_ok => Some(PendingAction::Delete),
-                _ => None,
-            },
+    /// Preview the selection after a jump. With `preview_delay_ms` the debounce picks it up
+    /// once the cursor rests, so only the immediate case is handled here.
+    pub fn follow_selection(&mut self) {
+        if self.config.preview_delay_ms.is_none() {
+            self.update_preview();
         }
     }
 
-    fn selected_any_path_and_name(&self) -> Option<(String, String)> {
-        let row = self.rows.get(self.cursor)?;
-        let entry = &self.entries[row.idx];
-        if entry.is_dir() {
-            let key = entry.store_key();
-            Some((key.clone(), key))
-        } else {
-            entry.relative_entry_path().map(|rel| (rel.clone(), rel))
-        }
+    pub fn half_page(&self) -> isize {
+        (self.list_height / 2).max(1) as isize
     }
 
-    fn path_exists(&self, rel: &str) -> bool {
-        let p = self.store_dir.join(rel);
-        if p.is_dir() {
-            return true;
-        }
-        let mut f = p.clone();
-        let _ = f.set_extension("gpg");
-        f.is_file()
+    /// List only the contents of `dir`, collapsed, with the cursor on the first row.
+    pub fn change_dir(&mut self, dir: &Path) {
+        self.set_cwd(dir);
+        // expanded keys are relative to the current directory
+        self.expanded.clear();
+        self.expanded.insert(String::new());
+        self.apply_filter();
+    }
+
+    /// Go up to the parent of the current directory. Returns false at the store root.
+    pub fn parent_dir(&mut self) -> bool {
+        let Some(parent) = self.cwd.parent() else {
+            return false;
+        };
+        let parent = parent.to_path_buf();
+        self.change_dir(&parent);
+        true
+    }
+
+    /// Expand every directory below the current directory.
+    pub fn expand_all(&mut self) {
+        let keys: Vec<DirKey> = (0..self.entries.len())
+            .filter(|&idx| {
+                let entry = &self.entries[idx];
+                entry.is_dir() && entry.path.starts_with(&self.cwd) && entry.path != self.cwd
+            })
+            .map(|idx| self.entry_key(idx))
+            .collect();
+        self.expanded.extend(keys);
+        self.apply_filter();
+    }
+
+    /// Collapse the tree back to the top-level entries.
+    pub fn collapse_all(&mut self) {
+        self.expanded.clear();
+        self.expanded.insert(String::new());
+        self.apply_filter();
+    }
+
+    pub fn enter(&mut self) {
+        if self.toggle_expand() {
+            return;
+        }
+        let plain = self
+            .rows
+            .get(self.cursor)
+            .is_some_and(|row| self.entries[row.idx].kind == EntryKind::Plain);
+        if plain {
+            self.update_preview();
+        }
+    }
+
+    /// Move to the 1-based row `number`, clamped to the list.
+    pub fn goto_row(&mut self, number: usize) {
+        self.cursor = number
+            .saturating_sub(1)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn selected_entry_path(&self) -> Option<String> {
+        self.rows
+            .get(self.cursor)
+            .and_then(|r| self.entries[r.idx].relative_entry_path())
+    }
+
+    /// The selected row if it has something to preview: a pass entry or a plain file.
+    pub fn selected_preview_key(&self) -> Option<String> {
+        let entry = &self.entries[self.rows.get(self.cursor)?.idx];
+        (!entry.is_dir()).then(|| entry.store_key())
+    }
+
+    /// Record an action in the audit log, if enabled.
+    pub fn audit<T>(&mut self, action: &str, entry: &str, result: &Result<T>) {
+        let Some(log) = &self.audit else {
+            return;
+        };
+        let outcome = match result {
+            Ok(_) => "ok".to_string(),
+            Err(err) => format!("error: {}", err),
+        };
+        if let Err(err) = log.record(action, entry, &outcome) {
+            self.set_status(format!("Audit log write failed: {}", err));
+        }
+    }
+
+    pub fn yank_selected(&mut self) {
+        let Some(rel) = self.selected_entry_path() else {
+            return self.set_status("Select a file to copy");
+        };
+        let result = self.yank_secret(&rel);
+        self.audit("yank", &rel, &result);
+        match result {
+            Ok(()) => self.start_clipboard_countdown("Copied password to clipboard".into()),
+            Err(e) => self.set_status(format!("Copy failed: {}", e)),
+        }
+    }
+
+    /// Copy the whole decrypted entry, every line, e.g. to paste it into a migration form.
+    /// The preview's decryption is reused when it shows the selected entry in full.
+    pub fn yank_all_selected(&mut self) {
+        let Some(rel) = self.selected_entry_path() else {
+            return self.set_status("Select a file to copy");
+        };
+        let previewed = self.preview_key.as_deref() == Some(rel.as_str())
+            && self.preview_loading.is_none()
+            && !self.preview_is_error
+            && !self.preview_truncated
+            && matches!(self.preview_mode, PreviewMode::Raw | PreviewMode::Fields);
+        let result = if previewed {
+            clipboard::copy_secret(self.backend.clipboard(), &self.preview_text)
+        } else {
+            self.backend.show(&rel).and_then(|mut content| {
+                let result = clipboard::copy_secret(self.backend.clipboard(), &content);
+                content.zeroize();
+                result
+            })
+        };
+        self.audit("yank_all", &rel, &result);
+        match result {
+            Ok(()) => self.start_clipboard_countdown(format!(
+                "Copied all of {}, every secret in it, to clipboard",
+                rel
+            )),
+            Err(e) => self.set_status(format!("Copy failed: {}", e)),
+        }
+    }
+
+    /// `pass -c` only ever copies line 1, so a labeled password is copied in-process instead.
+    fn yank_secret(&mut self, rel: &str) -> Result<()> {
+        let Some(label) = self.config.password_label.clone() else {
+            return self.backend.yank(rel);
+        };
+        let mut content = self.backend.show(rel)?;
+        let (secret, labeled) = fields::secret(&content, Some(&label));
+        let result = if labeled {
+            clipboard::copy_secret(self.backend.clipboard(), secret)
+        } else {
+            self.backend.yank(rel)
+        };
+        content.zeroize();
+        result
+    }
+
+    /// Open the `url:` of the selected entry (or the first link in it) in the browser.
+    pub fn open_url(&mut self) {
+        let Some(entry) = self.selected_entry_path() else {
+            return self.set_status("Select a file to open");
+        };
+        let result = self.backend.show(&entry);
+        self.audit("show", &entry, &result);
+        let mut content = match result {
+            Ok(content) => content,
+            Err(err) => return self.set_status(err.to_string()),
+        };
+        let url = fields::find_url(&content).map(str::to_string);
+        content.zeroize();
+        let Some(url) = url else {
+            return self.set_status(format!("No url field in {}", entry));
+        };
+        match browser::open(&url) {
+            Ok(()) => self.set_status(format!("Opened the url of {}", entry)),
+            Err(err) => self.set_status(err.to_string()),
+        }
+    }
+
+    /// List the `key: value` fields of the selected entry (names only) to pick one to copy.
+    pub fn open_field_picker(&mut self) {
+        let Some(rel) = self.selected_entry_path() else {
+            return;
+        };
+        let result = self.backend.show(&rel);
+        self.audit("show", &rel, &result);
+        let mut content = match result {
+            Ok(content) => content,
+            Err(e) => {
+                self.set_status(e.to_string());
+                return;
+            }
+        };
+        let mut items: Vec<String> = Vec::new();
+        for (key, _) in content.lines().skip(1).filter_map(fields::parse_field) {
+            if !items.iter().any(|seen| seen.eq_ignore_ascii_case(key)) {
+                items.push(key.to_string());
+            }
+        }
+        content.zeroize();
+        if items.is_empty() {
+            self.set_status(format!("No fields in {}", rel));
+            return;
+        }
+        self.modal = Some(Modal::Picker {
+            title: format!("Copy field of {}", rel),
+            items,
+            selected: 0,
+            action: ModalAction::YankField { entry: rel },
+        });
+    }
+
+    /// Run `pass git <args>` and report the last line of its output; the caller refreshes.
+    pub fn git_sync(&mut self, args: &[&str]) -> Result<()> {
+        let output = self.backend.git(args)?;
+        self.set_status(match last_line(&output) {
+            Some(line) => format!("git {}: {}", args.join(" "), line),
+            None => format!("git {}: done", args.join(" ")),
+        });
+        Ok(())
+    }
+
+    /// Copy the account name of the selected entry: its `login:`/`user:`/`username:`/`email:`
+    /// line, or else the entry's own name, as stores often name entries after the account.
+    pub fn yank_username(&mut self) {
+        let Some(entry) = self.selected_entry_path() else {
+            return;
+        };
+        let result = self.backend.show(&entry);
+        self.audit("yank_username", &entry, &result);
+        let mut content = match result {
+            Ok(content) => content,
+            Err(err) => return self.set_status(err.to_string()),
+        };
+        match fields::find_username(&content) {
+            Some((field, value)) => {
+                let note = format!("Copied {} of {}", field, entry);
+                match clipboard::copy_secret(self.backend.clipboard(), value) {
+                    Ok(()) => self.start_clipboard_countdown(note),
+                    Err(err) => self.set_status(err.to_string()),
+                }
+            }
+            None => {
+                let name = entry.rsplit('/').next().unwrap_or(&entry).to_string();
+                match clipboard::copy(self.backend.clipboard(), &name) {
+                    Ok(()) => self.set_status(format!(
+                        "No user field in {}; copied the entry name '{}'",
+                        entry, name
+                    )),
+                    Err(err) => self.set_status(err.to_string()),
+                }
+            }
+        }
+        content.zeroize();
+    }
+
+    /// Copy the next field of `copy_sequence` from the selected entry: the username on the first
+    /// press, the password on the second, for filling in login forms by hand. Moving to another
+    /// entry starts over.
+    pub fn copy_next_in_sequence(&mut self) {
+        let Some(entry) = self.selected_entry_path() else {
+            return self.set_status("Select a file to copy");
+        };
+        let sequence = self.config.copy_sequence.clone();
+        let step = match &self.copy_step {
+            Some((key, step)) if *key == entry => *step,
+            _ => 0,
+        };
+        let Some(field) = sequence.get(step) else {
+            return self.set_status("copy_sequence lists no fields");
+        };
+        let result = self.copy_sequence_field(&entry, field);
+        self.audit("yank", &format!("{} ({})", entry, field), &result);
+        let next = sequence.get(step + 1);
+        self.copy_step = next.map(|_| (entry.clone(), step + 1));
+        let hint = next
+            .map(|next| format!(" — press again for {}", next))
+            .unwrap_or_default();
+        match result {
+            Ok(true) => self.start_clipboard_countdown(format!("Copied {}{}", field, hint)),
+            Ok(false) => self.set_status(format!("No {} field in {}{}", field, entry, hint)),
+            Err(e) => {
+                self.copy_step = None;
+                self.set_status(format!("Copy failed: {}", e));
+            }
+        }
+    }
+
+    /// Copy one step of `copy_sequence`; `false` when the entry has no such field.
+    fn copy_sequence_field(&mut self, entry: &str, field: &str) -> Result<bool> {
+        if field == "password" {
+            // pass -c clears on its own schedule; ours must not cut it short
+            clipboard::cancel_pending_clear();
+            return self.yank_secret(entry).map(|()| true);
+        }
+        let mut content = self.backend.show(entry)?;
+        let value = if field == "username" {
+            fields::find_username(&content).map(|(_, value)| value)
+        } else {
+            fields::find_field(&content, field)
+        };
+        let result = match value {
+            Some(value) => clipboard::copy_secret(self.backend.clipboard(), value).map(|()| true),
+            None => Ok(false),
+        };
+        content.zeroize();
+        result
+    }
+
+    pub fn yank_field(&mut self, entry: &str, field: &str) -> Result<()> {
+        self.backend.yank_field(entry, field)?;
+        self.start_clipboard_countdown(format!("Copied {} of {}", field, entry));
+        Ok(())
+    }
+
+    /// Count down to the clipboard being cleared (`PASSWORD_STORE_CLIP_TIME`, 45s by default).
+    fn start_clipboard_countdown(&mut self, note: String) {
+        let secs = clipboard::clip_time();
+        self.clipboard_expires_at = Some(Instant::now() + Duration::from_secs(secs));
+        self.clipboard_note = note;
+        self.status = None;
+    }
+
+    /// Status text such as "Copied password to clipboard (clears in 38s)" while a countdown is
+    /// running.
+    pub fn clipboard_status(&self) -> Option<String> {
+        let expires_at = self.clipboard_expires_at?;
+        let remaining = expires_at.saturating_duration_since(Instant::now());
+        Some(format!(
+            "{} (clears in {}s)",
+            self.clipboard_note,
+            remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0)
+        ))
+    }
+
+    /// End the countdown once the clipboard has been cleared.
+    pub fn expire_clipboard(&mut self) {
+        if let Some(expires_at) = self.clipboard_expires_at {
+            if Instant::now() >= expires_at {
+                self.clipboard_expires_at = None;
+                self.clipboard_note.clear();
+            }
+        }
+    }
+
+    /// Store key of the selected row, for directories as well as entries.
+    pub fn selected_store_key(&self) -> Option<String> {
+        self.rows
+            .get(self.cursor)
+            .map(|row| self.entries[row.idx].store_key())
+    }
+
+    /// Copy the selected row's store key (what `pass show` or `pass ls` expects) without
+    /// decrypting anything. Works for directories as well as entries.
+    pub fn copy_selected_key(&mut self) {
+        if let Some(rel) = self.selected_store_key() {
+            self.set_status(match clipboard::copy(self.backend.clipboard(), &rel) {
+                Ok(()) => format!("Copied '{}' to clipboard", rel),
+                Err(e) => e.to_string(),
+            });
+        }
+    }
+
+    /// Where the selected row lives on disk: the encrypted file of an entry (`.gpg` or `.age`),
+    /// the file of a plain entry or the directory. With nothing selected, the store itself.
+    pub fn selected_disk_path(&self) -> PathBuf {
+        let Some(row) = self.rows.get(self.cursor) else {
+            return self.store_dir.clone();
+        };
+        let entry = &self.entries[row.idx];
+        let path = self.store_dir.join(&entry.path);
+        if entry.kind != EntryKind::Entry {
+            return path;
+        }
+        ["gpg", "age"]
+            .into_iter()
+            .map(|ext| encrypted_file(&path, ext))
+            .find(|file| file.is_file())
+            .unwrap_or_else(|| encrypted_file(&path, "gpg"))
+    }
+
+    /// Copy the selected row's absolute path on disk, also shown in the status line.
+    pub fn copy_selected_path(&mut self) {
+        let path = self.selected_disk_path().to_string_lossy().into_owned();
+        self.set_status(match clipboard::copy(self.backend.clipboard(), &path) {
+            Ok(()) => format!("Copied {}", path),
+            Err(e) => format!("{} (not copied: {})", path, e),
+        });
+    }
+
+    pub fn delete_selected(&mut self) -> Result<()> {
+        if let Some(row) = self.rows.get(self.cursor) {
+            let entry = &self.entries[row.idx];
+            if entry.is_dir() {
+                let rel = entry.store_key();
+                let parent = entry.path.parent().map(Path::to_path_buf);
+                self.backend.rm(&rel, true)?;
+                if self.config.prune_empty_dirs {
+                    if let Some(parent) = parent {
+                        self.offer_prune(&parent);
+                    }
+                }
+            } else if let Some(rel) = entry.relative_entry_path() {
+                self.backend.rm(&rel, false)?;
+            }
+            self.refresh()?;
+        }
+        Ok(())
+    }
+
+    fn offer_prune(&mut self, dir: &Path) {
+        if dir.as_os_str().is_empty() || !is_empty_dir(&self.store_dir.join(dir)) {
+            return;
+        }
+        let dir = path_to_store_key(dir);
+        self.modal = Some(Modal::Confirm {
+            title: "Remove Empty Directory".into(),
+            message: format!("'{}' is now empty. Remove it and any empty parents?", dir),
+            action: ModalAction::PruneEmpty { dir },
+            selected_ok: true,
+        });
+    }
+
+    pub fn prune_empty_parents(&mut self, dir: &str) -> Result<()> {
+        if self.config.dry_run {
+            // removed with fs, not pass, so the backend's dry run does not cover it
+            eprintln!("pass-tui: [dry-run] remove {} and any empty parents", dir);
+            self.set_status("Dry run: no directories removed");
+            return Ok(());
+        }
+        let removed = prune_empty_dirs(&self.store_dir, Path::new(dir))?;
+        self.set_status(format!("Removed {} empty directories", removed.len()));
+        self.refresh()
+    }
+
+    pub fn open_regenerate_modal(&mut self) {
+        if let Some(entry) = self.selected_entry_path() {
+            self.modal = Some(Modal::Confirm {
+                title: "Regenerate Password".into(),
+                message: format!("Replace the password of '{}'? Other lines are kept.", entry),
+                action: ModalAction::Regenerate { entry },
+                selected_ok: false,
+            });
+        }
+    }
+
+    pub fn regenerate(&mut self, entry: &str) -> Result<()> {
+        let result = self.backend.generate_in_place(
+            entry,
+            self.config.generate_length,
+            self.config.generate_no_symbols,
+        );
+        self.audit("regenerate", entry, &result);
+        result?;
+        // the cached preview now shows the old password
+        self.preview_key = None;
+        self.yank_secret(entry)?;
+        self.start_clipboard_countdown(format!(
+            "Regenerated password for '{}' and copied it",
+            entry
+        ));
+        Ok(())
+    }
+
+    /// Show who can decrypt the selected entry or directory.
+    pub fn open_recipients_modal(&mut self) {
+        let Some(key) = self.selected_store_key() else {
+            return;
+        };
+        match self.backend.recipients(&key) {
+            Ok(recipients) if recipients.is_empty() => {
+                self.set_status(format!("The .gpg-id for {} lists no recipients", key));
+            }
+            Ok(recipients) => {
+                self.modal = Some(Modal::Info {
+                    title: format!("Recipients of {}", key),
+                    lines: recipients,
+                });
+            }
+            Err(e) => self.set_status(e.to_string()),
+        }
+    }
+
+    /// List the latest commits that touched the selected entry or directory.
+    pub fn open_git_log_modal(&mut self) {
+        let Some(key) = self.selected_store_key() else {
+            return;
+        };
+        match self.backend.git_log(&key, GIT_LOG_LEN) {
+            Ok(commits) if commits.is_empty() => {
+                self.set_status(format!("No commits touch {}", key));
+            }
+            Ok(commits) => {
+                self.modal = Some(Modal::Info {
+                    title: format!("History of {}", key),
+                    lines: commits,
+                });
+            }
+            Err(e) => self.set_status(e.to_string()),
+        }
+    }
+
+    /// Show `message` in the status line and keep it in the message log.
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        if self.messages.len() == MESSAGE_LOG_LEN {
+            self.messages.remove(0);
+        }
+        self.messages.push((Instant::now(), message.clone()));
+        self.status = Some(message);
+    }
+
+    /// List recent status messages, newest first.
+    pub fn open_messages_modal(&mut self) {
+        let now = SystemTime::now();
+        let lines = if self.messages.is_empty() {
+            vec!["No messages yet".to_string()]
+        } else {
+            self.messages
+                .iter()
+                .rev()
+                .map(|(at, message)| {
+                    format!("{:>9}  {}", format_age(now - at.elapsed(), now), message)
+                })
+                .collect()
+        };
+        self.modal = Some(Modal::Info {
+            title: "Messages".into(),
+            lines,
+        });
+    }
+
+    pub fn open_diagnostics_modal(&mut self) {
+        let entry_count = self
+            .entries
+            .iter()
+            .filter(|e| e.kind == EntryKind::Entry)
+            .count();
+        let lines = diagnostics::collect(&self.store_dir, entry_count)
+            .into_iter()
+            .map(|(label, value)| format!("{}: {}", label, value))
+            .collect();
+        self.modal = Some(Modal::Info {
+            title: "Diagnostics".into(),
+            lines,
+        });
+    }
+
+    /// Decrypt the selected entry and show only its first line until the flash deadline.
+    pub fn flash_password(&mut self) {
+        let Some(entry) = self.selected_entry_path() else {
+            return;
+        };
+        let result = self.backend.show(&entry);
+        self.audit("flash", &entry, &result);
+        match result {
+            Ok(mut text) => {
+                let label = self.config.password_label.as_deref();
+                let secret = fields::secret(&text, label).0.to_string();
+                text.zeroize();
+                self.close_modal();
+                self.modal = Some(Modal::Flash { entry, secret });
+                self.flash_deadline =
+                    Some(Instant::now() + Duration::from_secs(self.config.flash_seconds));
+            }
+            Err(err) => self.set_status(err.to_string()),
+        }
+    }
+
+    /// Show the selected entry's password in the status line for `flash_seconds`, leaving the
+    /// preview pane as it is.
+    pub fn peek_password(&mut self) {
+        let Some(entry) = self.selected_entry_path() else {
+            return;
+        };
+        let result = self.backend.show(&entry);
+        self.audit("peek", &entry, &result);
+        match result {
+            Ok(mut text) => {
+                let label = self.config.password_label.as_deref();
+                let secret = fields::secret(&text, label).0.to_string();
+                text.zeroize();
+                self.clear_peek();
+                self.peek = Some(secret);
+                self.peek_deadline =
+                    Some(Instant::now() + Duration::from_secs(self.config.flash_seconds));
+            }
+            Err(err) => self.set_status(err.to_string()),
+        }
+    }
+
+    /// Wipe an expired peek; returns whether the screen changed.
+    pub fn expire_peek(&mut self) -> bool {
+        match self.peek_deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.clear_peek();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn clear_peek(&mut self) {
+        if let Some(mut secret) = self.peek.take() {
+            secret.zeroize();
+        }
+        self.peek_deadline = None;
+    }
+
+    /// Hide an expired flash; returns whether the screen changed.
+    pub fn expire_flash(&mut self) -> bool {
+        match self.flash_deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.close_modal();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Dismiss the current modal, wiping any secret it was displaying.
+    pub fn close_modal(&mut self) {
+        match self.modal.take() {
+            Some(Modal::Flash { mut secret, .. }) => secret.zeroize(),
+            Some(Modal::Multiline { mut buffer, .. }) => buffer.zeroize(),
+            Some(Modal::Input {
+                mut buffer,
+                action: ModalAction::EditLine { .. },
+                ..
+            }) => buffer.zeroize(),
+            _ => {}
+        }
+        self.flash_deadline = None;
+    }
+
+    /// Prefill with absolute path (within store). If hovering a directory, prefill "dir/".
+    /// With nothing listed, fall back to the current directory (the root in an empty store).
+    fn new_entry_prefix(&self) -> String {
+        let mut prefix = match self.rows.get(self.cursor) {
+            Some(row) => {
+                let entry = &self.entries[row.idx];
+                if entry.is_dir() {
+                    entry.store_key()
+                } else {
+                    entry
+                        .path
+                        .parent()
+                        .map(path_to_store_key)
+                        .unwrap_or_default()
+                }
+            }
+            None => path_to_store_key(&self.cwd),
+        };
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix
+    }
+
+    pub fn start_jump(&mut self) {
+        self.jump_prefix = Some(String::new());
+        self.jump_deadline = Some(Instant::now() + JUMP_TIMEOUT);
+    }
+
+    pub fn end_jump(&mut self) {
+        self.jump_prefix = None;
+        self.jump_deadline = None;
+    }
+
+    /// Leave jump mode after a pause in typing.
+    pub fn expire_jump(&mut self) {
+        if self
+            .jump_deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.end_jump();
+        }
+    }
+
+    /// Append to (or with `None`, shorten) the jump prefix and move to the first row at or
+    /// after the cursor, wrapping around, whose name starts with it. Rows stay visible.
+    pub fn jump(&mut self, c: Option<char>) {
+        let Some(prefix) = self.jump_prefix.as_mut() else {
+            return;
+        };
+        match c {
+            Some(c) => prefix.extend(c.to_lowercase()),
+            None => {
+                prefix.pop();
+            }
+        }
+        self.jump_deadline = Some(Instant::now() + JUMP_TIMEOUT);
+        let prefix = prefix.clone();
+        if prefix.is_empty() || self.rows.is_empty() {
+            return;
+        }
+        let len = self.rows.len();
+        let found = (0..len)
+            .map(|step| (self.cursor + step) % len)
+            .find(|&pos| {
+                let name = self.entries[self.rows[pos].idx].display_name();
+                name.to_lowercase().starts_with(&prefix)
+            });
+        match found {
+            Some(pos) => self.cursor = pos,
+            None => self.set_status(format!("No entry starts with '{}'", prefix)),
+        }
+    }
+
+    pub fn open_add_modal(&mut self) {
+        self.modal = Some(Modal::Input {
+            title: "New entry path".into(),
+            buffer: self.new_entry_prefix(),
+            action: ModalAction::AddHere,
+            error: None,
+        });
+    }
+
+    /// Ask for a path, then for the contents typed or pasted into a multiline box.
+    pub fn open_insert_modal(&mut self) {
+        self.modal = Some(Modal::Input {
+            title: "New entry path (insert)".into(),
+            buffer: self.new_entry_prefix(),
+            action: ModalAction::InsertPath,
+            error: None,
+        });
+    }
+
+    /// Like insert, but the contents start out as the entry template.
+    pub fn open_template_modal(&mut self) {
+        let Some(path) = self.config.template_path() else {
+            return self.set_status("No config directory for the entry template");
+        };
+        if !path.is_file() {
+            return self.set_status(format!("No entry template at {}", path.display()));
+        }
+        self.modal = Some(Modal::Input {
+            title: "New entry path (template)".into(),
+            buffer: self.new_entry_prefix(),
+            action: ModalAction::TemplatePath,
+            error: None,
+        });
+    }
+
+    pub fn insert(&mut self, entry: &str, mut content: String) -> Result<()> {
+        let result = self.backend.insert(entry, &content, true);
+        content.zeroize();
+        result
+    }
+
+    /// Edit the password line of the selected entry in place, without `$EDITOR`.
+    pub fn open_edit_line_modal(&mut self) {
+        let Some(entry) = self.selected_entry_path() else {
+            return self.set_status("Select a file to edit");
+        };
+        let result = self.backend.show(&entry);
+        self.audit("show", &entry, &result);
+        let mut content = match result {
+            Ok(content) => content,
+            Err(err) => return self.set_status(err.to_string()),
+        };
+        let buffer = content.lines().next().unwrap_or_default().to_string();
+        content.zeroize();
+        self.modal = Some(Modal::Input {
+            title: format!("First line of {}", entry),
+            buffer,
+            action: ModalAction::EditLine { entry },
+            error: None,
+        });
+    }
+
+    /// Rewrite `entry` with `line` in place of its first line. The entry is decrypted again
+    /// rather than kept from when the modal opened, so the other lines are current.
+    pub fn edit_line(&mut self, entry: &str, mut line: String) -> Result<()> {
+        let mut content = self.backend.show(entry)?;
+        let mut updated = replace_first_line(&content, &line);
+        content.zeroize();
+        line.zeroize();
+        let result = self.backend.replace(entry, &updated);
+        updated.zeroize();
+        result?;
+        // the cached preview now shows the old line
+        self.preview_key = None;
+        self.set_status(format!("Updated the first line of '{}'", entry));
+        Ok(())
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        // record where the entry really went, which differs when moving into a directory
+        let to = move_destination(&self.store_dir, from, to)?;
+        self.backend.mv(from, &to)?;
+        // follow the entry to its new place; the refresh after the action keeps it there
+        self.refresh()?;
+        self.select_key(&to);
+        // a dry run moved nothing, so there is nothing to move back
+        if !self.config.dry_run {
+            self.undo_stack.push(UndoOp::Rename {
+                from: from.to_string(),
+                to,
+            });
+        }
+        Ok(())
+    }
+
+    /// Work out where each marked entry goes when moved into `dir` (the store root when empty),
+    /// refusing the whole move if any of them would clash.
+    fn plan_moves(&self, keys: &[String], dir: &str) -> Result<Vec<(String, String)>> {
+        let dir = dir.trim().trim_end_matches('/');
+        let dir = if dir.is_empty() {
+            String::new()
+        } else {
+            normalize_entry_path(dir).map_err(|err| anyhow::anyhow!(err))?
+        };
+        let mut moves = Vec::new();
+        let mut targets = HashSet::new();
+        for key in keys {
+            let name = key.rsplit('/').next().unwrap_or(key);
+            let to = if dir.is_empty() {
+                name.to_string()
+            } else {
+                format!("{dir}/{name}")
+            };
+            if &to == key {
+                continue;
+            }
+            if !targets.insert(to.clone()) {
+                anyhow::bail!("more than one selected entry is named '{}'", name);
+            }
+            move_destination(&self.store_dir, key, &to)?;
+            moves.push((key.clone(), to));
+        }
+        Ok(moves)
+    }
+
+    /// Ask for text to replace in the names of all entries, starting from the current directory.
+    pub fn open_bulk_rename_modal(&mut self) {
+        let mut buffer = path_to_store_key(&self.cwd);
+        if !buffer.is_empty() {
+            buffer.push('/');
+        }
+        self.modal = Some(Modal::Input {
+            title: "Find in entry names".into(),
+            buffer,
+            action: ModalAction::BulkFind,
+            error: None,
+        });
+    }
+
+    /// Moves renaming every entry whose key contains `find`, and the ones left out as
+    /// `key: reason`: a destination that exists, is claimed twice or is not a valid path.
+    pub fn plan_bulk_rename(
+        &self,
+        find: &str,
+        replace: &str,
+    ) -> (Vec<(String, String)>, Vec<String>) {
+        let mut moves = Vec::new();
+        let mut skipped = Vec::new();
+        let mut targets = HashSet::new();
+        let mut keys: Vec<String> = self
+            .entries
+            .iter()
+            .filter_map(StoreEntry::relative_entry_path)
+            .filter(|key| key.contains(find))
+            .collect();
+        keys.sort();
+        for key in keys {
+            let to = match normalize_entry_path(&key.replace(find, replace)) {
+                Ok(to) if to == key => continue,
+                Ok(to) => to,
+                Err(err) => {
+                    skipped.push(format!("{key}: {err}"));
+                    continue;
+                }
+            };
+            if self.path_exists(&to) {
+                skipped.push(format!("{key}: {to} exists"));
+            } else if !targets.insert(to.clone()) {
+                skipped.push(format!("{key}: {to} is taken by another entry"));
+            } else {
+                moves.push((key, to));
+            }
+        }
+        (moves, skipped)
+    }
+
+    /// List the planned renames and skips, and ask before moving anything.
+    fn confirm_bulk_rename(&mut self, find: &str, replace: &str) {
+        let (moves, skipped) = self.plan_bulk_rename(find, replace);
+        if moves.is_empty() {
+            return self.set_status(match skipped.first() {
+                None => format!("No entry names contain '{}'", find),
+                Some(first) => format!("Nothing to rename; skipped {}", first),
+            });
+        }
+        let noun = if moves.len() == 1 { "entry" } else { "entries" };
+        let mut lines = vec![format!("Rename {} {}?", moves.len(), noun)];
+        lines.extend(
+            moves
+                .iter()
+                .take(BULK_PREVIEW_LEN)
+                .map(|(from, to)| format!("  {} -> {}", from, to)),
+        );
+        if moves.len() > BULK_PREVIEW_LEN {
+            lines.push(format!("  … and {} more", moves.len() - BULK_PREVIEW_LEN));
+        }
+        if !skipped.is_empty() {
+            lines.push(format!("Skipping {}:", skipped.len()));
+            lines.extend(
+                skipped
+                    .iter()
+                    .take(BULK_PREVIEW_LEN)
+                    .map(|skip| format!("  {}", skip)),
+            );
+        }
+        self.modal = Some(Modal::Confirm {
+            title: "Rename Entries".into(),
+            message: lines.join("\n"),
+            action: ModalAction::BulkRename { moves },
+            selected_ok: false,
+        });
+    }
+
+    /// Delete each key, going on past failures, then clear the selection.
+    pub fn delete_many(&mut self, keys: &[String]) -> Result<()> {
+        let mut failed = Vec::new();
+        for key in keys {
+            let recursive = self.store_dir.join(key).is_dir();
+            let result = self.backend.rm(key, recursive);
+            self.audit("delete", key, &result);
+            if let Err(err) = result {
+                failed.push(format!("{key}: {err}"));
+            }
+        }
+        self.selection.clear();
+        self.finish_bulk("Deleted", keys.len(), failed)
+    }
+
+    /// Move each entry, recording every move for undo, then clear the selection.
+    pub fn move_many(&mut self, moves: &[(String, String)]) -> Result<()> {
+        let mut failed = Vec::new();
+        for (from, to) in moves {
+            let result = self.rename(from, to);
+            self.audit("rename", &format!("{} -> {}", from, to), &result);
+            if let Err(err) = result {
+                failed.push(format!("{from}: {err}"));
+            }
+        }
+        self.selection.clear();
+        self.finish_bulk("Moved", moves.len(), failed)
+    }
+
+    fn finish_bulk(&mut self, verb: &str, total: usize, failed: Vec<String>) -> Result<()> {
+        let done = total - failed.len();
+        match failed.first() {
+            None => {
+                self.set_status(format!("{verb} {done} of {total}"));
+                Ok(())
+            }
+            Some(first) if failed.len() == 1 => {
+                anyhow::bail!("{verb} {done} of {total}; failed {first}")
+            }
+            Some(first) => anyhow::bail!(
+                "{verb} {done} of {total}; failed {first} and {} more",
+                failed.len() - 1
+            ),
+        }
+    }
+
+    /// Duplicate `from` and select the copy.
+    pub fn copy(&mut self, from: &str, to: &str) -> Result<()> {
+        let to = move_destination(&self.store_dir, from, to)?;
+        self.backend.cp(from, &to)?;
+        self.refresh()?;
+        self.select_key(&to);
+        self.set_status(format!("Copied '{}' to '{}'", from, to));
+        Ok(())
+    }
+
+    /// Reverse the most recent undoable operation. Deletes are never recorded: they cannot be
+    /// brought back from here.
+    pub fn undo(&mut self) -> Result<()> {
+        let Some(op) = self.undo_stack.pop() else {
+            self.set_status("Nothing to undo (deletes cannot be undone)");
+            return Ok(());
+        };
+        match &op {
+            UndoOp::Rename { from, to } => {
+                let result = self.backend.mv(to, from);
+                self.audit("rename", &format!("{} -> {}", to, from), &result);
+                if let Err(err) = result {
+                    // keep it so the user can retry once the problem is fixed
+                    self.undo_stack.push(op.clone());
+                    return Err(err);
+                }
+                self.set_status(format!("Undid rename: '{}' is back at '{}'", to, from));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn open_grep_modal(&mut self) {
+        self.modal = Some(Modal::Input {
+            title: "Search decrypted contents (pass grep)".into(),
+            buffer: self
+                .grep
+                .as_ref()
+                .map(|(pattern, _)| pattern.clone())
+                .unwrap_or_default(),
+            action: ModalAction::Grep,
+            error: None,
+        });
+    }
+
+    /// Limit the list to entries whose contents match `pattern`.
+    pub fn grep(&mut self, pattern: &str) -> Result<()> {
+        let result = self.backend.grep(pattern);
+        self.audit("grep", pattern, &result);
+        let matches: HashSet<String> = result?.into_iter().collect();
+        self.set_status(format!("{} entries match '{}'", matches.len(), pattern));
+        self.grep = Some((pattern.to_string(), matches));
+        self.cursor = 0;
+        self.apply_filter();
+        Ok(())
+    }
+
+    /// Mark or unmark the row under the cursor and move on to the next one.
+    pub fn toggle_selection(&mut self) {
+        let Some(row) = self.rows.get(self.cursor) else {
+            return;
+        };
+        let idx = row.idx;
+        if self.entries[idx].kind == EntryKind::Plain {
+            return;
+        }
+        if !self.selection.remove(&idx) {
+            self.selection.insert(idx);
+        }
+        let message = match self.selection.len() {
+            0 => "Selection cleared".to_string(),
+            count => format!("{count} selected — d deletes, r moves them, Esc clears"),
+        };
+        self.set_status(message);
+        self.move_cursor(1);
+    }
+
+    /// Store keys of the marked rows, sorted, leaving out those inside a marked directory
+    /// since deleting or moving the directory takes them along.
+    pub fn marked_keys(&self) -> Vec<String> {
+        let paths: Vec<&Path> = self
+            .selection
+            .iter()
+            .map(|&idx| self.entries[idx].path.as_path())
+            .collect();
+        let mut keys: Vec<String> = paths
+            .iter()
+            .filter(|path| {
+                !paths
+                    .iter()
+                    .any(|other| other != *path && path.starts_with(other))
+            })
+            .map(|path| path_to_store_key(path))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn open_rename_modal(&mut self) {
+        if !self.selection.is_empty() {
+            let keys = self.marked_keys();
+            self.modal = Some(Modal::Input {
+                title: format!("Move {} selected into directory", keys.len()),
+                buffer: path_to_store_key(&self.cwd),
+                action: ModalAction::MoveMarked { keys },
+                error: None,
+            });
+            return;
+        }
+        if let Some((from, suggested)) = self.selected_any_path_and_name() {
+            self.modal = Some(Modal::Input {
+                title: "Rename entry".into(),
+                buffer: suggested,
+                action: ModalAction::Rename { from },
+                error: None,
+            });
+        }
+    }
+
+    pub fn open_copy_modal(&mut self) {
+        if let Some((from, suggested)) = self.selected_any_path_and_name() {
+            self.modal = Some(Modal::Input {
+                title: "Copy to".into(),
+                buffer: suggested,
+                action: ModalAction::Copy { from },
+                error: None,
+            });
+        }
+    }
+
+    pub fn open_delete_modal(&mut self) {
+        let Some(row) = self.rows.get(self.cursor) else {
+            return;
+        };
+        if !self.selection.is_empty() {
+            let keys = self.marked_keys();
+            let noun = if keys.len() == 1 { "entry" } else { "entries" };
+            self.modal = Some(Modal::Confirm {
+                title: "Confirm Delete".into(),
+                message: format!(
+                    "Delete {} selected {}? Selected directories go with everything in them. This cannot be undone.",
+                    keys.len(),
+                    noun
+                ),
+                action: ModalAction::DeleteMarked { keys },
+                selected_ok: false,
+            });
+            return;
+        }
+        let entry = &self.entries[row.idx];
+        if entry.kind == EntryKind::Plain {
+            let message = format!("'{}' is not a pass entry", entry.store_key());
+            self.set_status(message);
+            return;
+        }
+        let (message, selected_ok) = if entry.is_dir() {
+            let count = self
+                .entries
+                .iter()
+                .filter(|e| e.kind == EntryKind::Entry && e.path.starts_with(&entry.path))
+                .count();
+            let noun = if count == 1 { "entry" } else { "entries" };
+            // recursive deletes default to Cancel so a stray Enter cannot remove a whole tree
+            (
+                format!(
+                    "Delete directory '{}' and its {} {}? This cannot be undone.",
+                    entry.store_key(),
+                    count,
+                    noun
+                ),
+                false,
+            )
+        } else {
+            (
+                format!("Delete '{}'? This cannot be undone.", entry.store_key()),
+                true,
+            )
+        };
+        self.modal = Some(Modal::Confirm {
+            title: "Confirm Delete".into(),
+            message,
+            action: ModalAction::DeleteSelected,
+            selected_ok,
+        });
+    }
+
+    pub fn submit_modal(&mut self) -> Option<PendingAction> {
+        let modal = self.modal.take()?;
+        match modal {
+            Modal::Input {
+                title,
+                buffer,
+                action:
+                    action
+                    @ (ModalAction::AddHere | ModalAction::InsertPath | ModalAction::TemplatePath),
+                ..
+            } => {
+                let entry = normalize_entry_path(&buffer).and_then(|entry| {
+                    if self.backend.has_recipients(&entry) {
+                        Ok(entry)
+                    } else {
+                        Err(NO_GPG_ID.to_string())
+                    }
+                });
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        // keep the modal open so the path can be fixed
+                        self.modal = Some(Modal::Input {
+                            title,
+                            buffer,
+                            action,
+                            error: Some(error),
+                        });
+                        return None;
+                    }
+                };
+                if matches!(action, ModalAction::AddHere) {
+                    if self.entry_exists(&entry) {
+                        // pass edit would open the existing entry rather than start a new one
+                        self.modal = Some(Modal::Confirm {
+                            title: "Entry Exists".into(),
+                            message: format!("'{}' already exists. Edit it instead?", entry),
+                            action: ModalAction::EditExisting { entry },
+                            selected_ok: false,
+                        });
+                        return None;
+                    }
+                    if self.path_exists(&entry) {
+                        self.set_status(format!("'{}' is a directory — add aborted", entry));
+                        return None;
+                    }
+                    return Some(PendingAction::Add(entry));
+                }
+                if self.path_exists(&entry) {
+                    self.set_status(format!("'{}' already exists — insert aborted", entry));
+                    return None;
+                }
+                let buffer = if matches!(action, ModalAction::TemplatePath) {
+                    let template = self.config.template_path().map(fs::read_to_string);
+                    match template {
+                        Some(Ok(template)) => fill_template(&template, &entry),
+                        Some(Err(err)) => {
+                            self.set_status(format!("Could not read the entry template: {}", err));
+                            return None;
+                        }
+                        None => String::new(),
+                    }
+                } else {
+                    String::new()
+                };
+                self.modal = Some(Modal::Multiline {
+                    title: format!("Contents of {}", entry),
+                    buffer,
+                    action: ModalAction::Insert { entry },
+                });
+                None
+            }
+            Modal::Input { action, buffer, .. } => match action {
+                ModalAction::AddHere
+                | ModalAction::InsertPath
+                | ModalAction::TemplatePath
+                | ModalAction::Insert { .. }
+                | ModalAction::EditExisting { .. }
+                | ModalAction::BulkRename { .. }
+                | ModalAction::DeleteSelected
+                | ModalAction::DeleteMarked { .. }
+                | ModalAction::PruneEmpty { .. }
+                | ModalAction::Regenerate { .. }
+                | ModalAction::SwitchProfile
+                | ModalAction::SwitchStore
+                | ModalAction::YankField { .. } => None,
+                ModalAction::BulkFind if buffer.is_empty() => None,
+                ModalAction::BulkFind => {
+                    self.modal = Some(Modal::Input {
+                        title: format!("Replace '{}' with", buffer),
+                        buffer: buffer.clone(),
+                        action: ModalAction::BulkReplace { find: buffer },
+                        error: None,
+                    });
+                    None
+                }
+                ModalAction::BulkReplace { find } => {
+                    self.confirm_bulk_rename(&find, &buffer);
+                    None
+                }
+                ModalAction::MoveMarked { keys } => match self.plan_moves(&keys, &buffer) {
+                    Ok(moves) if moves.is_empty() => None,
+                    Ok(moves) => Some(PendingAction::MoveMany(moves)),
+                    Err(err) => {
+                        self.set_status(format!("{} — move aborted", err));
+                        None
+                    }
+                },
+                ModalAction::EditLine { entry } if !buffer.is_empty() => {
+                    Some(PendingAction::EditLine {
+                        entry,
+                        line: buffer,
+                    })
+                }
+                ModalAction::EditLine { .. } => {
+                    let mut buffer = buffer;
+                    buffer.zeroize();
+                    None
+                }
+                ModalAction::Grep => {
+                    let pattern = buffer.trim();
+                    if pattern.is_empty() {
+                        None
+                    } else {
+                        Some(PendingAction::Grep(pattern.to_string()))
+                    }
+                }
+                ModalAction::Copy { from } => {
+                    let to = buffer.trim();
+                    if to.is_empty() || to == from {
+                        return None;
+                    }
+                    if let Err(err) = move_destination(&self.store_dir, &from, to) {
+                        self.set_status(format!("{} — copy aborted", err));
+                        None
+                    } else {
+                        Some(PendingAction::Copy {
+                            from,
+                            to: to.to_string(),
+                        })
+                    }
+                }
+                ModalAction::Rename { from } => {
+                    let to = buffer.trim();
+                    if to.is_empty() || to == from {
+                        return None;
+                    }
+                    if let Err(err) = move_destination(&self.store_dir, &from, to) {
+                        self.set_status(format!("{} — rename aborted", err));
+                        None
+                    } else {
+                        Some(PendingAction::Rename {
+                            from,
+                            to: to.to_string(),
+                        })
+                    }
+                }
+            },
+            Modal::Confirm {
+                action,
+                selected_ok,
+                ..
+            } => match action {
+                ModalAction::DeleteSelected if selected_ok => Some(PendingAction::Delete),
+                ModalAction::DeleteMarked { keys } if selected_ok => {
+                    Some(PendingAction::DeleteMany(keys))
+                }
+                ModalAction::PruneEmpty { dir } if selected_ok => Some(PendingAction::Prune(dir)),
+                ModalAction::Regenerate { entry } if selected_ok => {
+                    Some(PendingAction::Regenerate(entry))
+                }
+                ModalAction::EditExisting { entry } if selected_ok => {
+                    Some(PendingAction::Edit(entry))
+                }
+                ModalAction::BulkRename { moves } if selected_ok => {
+                    Some(PendingAction::MoveMany(moves))
+                }
+                _ => None,
+            },
+            Modal::Info { .. } => None,
+            Modal::Multiline { action, buffer, .. } => match action {
+                ModalAction::Insert { entry } if !buffer.trim().is_empty() => {
+                    Some(PendingAction::Insert {
+                        entry,
+                        content: buffer,
+                    })
+                }
+                _ => {
+                    let mut buffer = buffer;
+                    buffer.zeroize();
+                    None
+                }
+            },
+            Modal::Picker {
+                items,
+                selected,
+                action,
+                ..
+            } => {
+                let item = items.into_iter().nth(selected)?;
+                match action {
+                    ModalAction::SwitchProfile => Some(PendingAction::SwitchProfile(item)),
+                    ModalAction::SwitchStore => Some(PendingAction::SwitchStore(item.into())),
+                    ModalAction::YankField { entry } => {
+                        Some(PendingAction::YankField { entry, field: item })
+                    }
+                    _ => None,
+                }
+            }
+            Modal::Flash { mut secret, .. } => {
+                secret.zeroize();
+                self.flash_deadline = None;
+                None
+            }
+        }
+    }
+
+    fn selected_any_path_and_name(&self) -> Option<(String, String)> {
+        let row = self.rows.get(self.cursor)?;
+        let entry = &self.entries[row.idx];
+        if entry.is_dir() {
+            let key = entry.store_key();
+            Some((key.clone(), key))
+        } else {
+            entry.relative_entry_path().map(|rel| (rel.clone(), rel))
+        }
+    }
+
+    fn path_exists(&self, rel: &str) -> bool {
+        self.store_dir.join(rel).is_dir() || self.entry_exists(rel)
+    }
+
+    /// Whether `rel` is an encrypted entry, as opposed to a directory or nothing at all.
+    fn entry_exists(&self, rel: &str) -> bool {
+        let path = self.store_dir.join(rel);
+        ["gpg", "age"]
+            .iter()
+            .any(|ext| encrypted_file(&path, ext).is_file())
     }
 
     fn set_preview_state(&mut self, rel: String, text: String, is_error: bool, mode: PreviewMode) {
+        if self.preview_key.as_deref() != Some(rel.as_str()) {
+            self.preview_scroll = 0;
+            self.preview_hscroll = 0;
+            self.preview_masked = true;
+        }
+        self.preview_strength = match mode {
+            PreviewMode::Raw | PreviewMode::Fields if !is_error => {
+                let label = self.config.password_label.as_deref();
+                strength::estimate(fields::secret(&text, label).0)
+            }
+            _ => None,
+        };
         self.preview_key = Some(rel);
         self.preview_text = text;
         self.preview_is_error = is_error;
+        self.preview_truncated = false;
         self.preview_mode = mode;
     }
 
-    fn load_preview(&mut self, rel: String, mode: PreviewMode, allow_unlock: bool) -> Result<()> {
-        let result = match mode {
-            PreviewMode::Raw => self.backend.show(&rel),
-            PreviewMode::Qr => self.backend.show_qr(&rel),
+    /// Decrypt `rel` on a worker thread; the result is picked up by `poll_preview`.
+    fn load_preview(&mut self, rel: String, mode: PreviewMode, allow_unlock: bool) {
+        self.preview_requests += 1;
+        let id = self.preview_requests;
+        self.preview_loading = Some(id);
+        self.set_preview_state(rel.clone(), String::new(), false, mode);
+
+        let plain = self
+            .entries
+            .iter()
+            .any(|e| e.kind == EntryKind::Plain && e.store_key() == rel);
+        let plain_path = plain.then(|| self.store_dir.join(&rel));
+        let backend = Arc::clone(&self.backend);
+        let tx = self.preview_tx.clone();
+        thread::spawn(move || {
+            let result = match (plain_path, mode) {
+                (Some(path), _) => fs::read_to_string(path).map_err(Into::into),
+                (None, PreviewMode::Raw | PreviewMode::Fields) => backend.show(&rel),
+                (None, PreviewMode::Qr) => backend.secret_for_qr(&rel),
+                (None, PreviewMode::Otp) => backend.otp(&rel),
+            };
+            let _ = tx.send(PreviewResult {
+                id,
+                rel,
+                mode,
+                allow_unlock,
+                plain,
+                result,
+            });
+        });
+    }
+
+    /// Apply the preview the worker finished, if any. Returns whether the screen needs a redraw.
+    pub fn poll_preview(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(mut done) = self.preview_rx.try_recv() {
+            if self.preview_loading != Some(done.id) {
+                // the selection moved on while this one was decrypting
+                if let Ok(text) = &mut done.result {
+                    text.zeroize();
+                }
+                continue;
+            }
+            self.preview_loading = None;
+            self.finish_preview(done);
+            changed = true;
+        }
+        changed
+    }
+
+    fn finish_preview(&mut self, done: PreviewResult) {
+        let PreviewResult {
+            rel,
+            mode,
+            allow_unlock,
+            plain,
+            result,
+            ..
+        } = done;
+        let action = if mode == PreviewMode::Otp {
+            "otp"
+        } else {
+            "show"
         };
+        self.audit(action, &rel, &result);
         match result {
             Ok(text) => {
                 self.pending_preview = None;
+                let (text, truncated) = truncate_preview(text, self.config.preview_max_kb * 1024);
                 self.set_preview_state(rel, text, false, mode);
-                Ok(())
+                self.preview_truncated = truncated;
+                if plain {
+                    self.preview_masked = false;
+                }
             }
             Err(err) => {
                 if !allow_unlock {
                     if let Some(status_err) = err.downcast_ref::<PassStatusError>() {
-                        if status_err.status.code() == Some(2) {
+                        if status_err.needs_unlock() {
                             self.pending_preview = Some((rel.clone(), mode));
                             self.set_preview_state(
                                 rel,
@@ -391,36 +2580,65 @@ impl App {
                                 true,
                                 mode,
                             );
-                            return Ok(());
+                            return;
                         }
                     }
                 }
                 let message = err.to_string();
                 self.set_preview_state(rel, message.clone(), true, mode);
-                Err(err)
+                self.set_status(message);
             }
         }
     }
 
+    /// Scroll the preview by `delta` lines, keeping at least the last line in view.
+    pub fn scroll_preview(&mut self, delta: i32) {
+        let max = self.preview_text.lines().count().saturating_sub(1);
+        let max = u16::try_from(max).unwrap_or(u16::MAX);
+        let next = i32::from(self.preview_scroll) + delta;
+        self.preview_scroll = next.clamp(0, i32::from(max)) as u16;
+    }
+
+    /// Pan the unwrapped preview sideways, stopping once the longest line is in view.
+    pub fn pan_preview(&mut self, delta: i32) {
+        let max = self
+            .preview_text
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(1);
+        let max = u16::try_from(max).unwrap_or(u16::MAX);
+        let next = i32::from(self.preview_hscroll) + delta;
+        self.preview_hscroll = next.clamp(0, i32::from(max)) as u16;
+    }
+
+    pub fn toggle_preview_wrap(&mut self) {
+        self.preview_wrap = !self.preview_wrap;
+        self.preview_hscroll = 0;
+        self.set_status(if self.preview_wrap {
+            "Preview wraps long lines"
+        } else {
+            "Preview cuts long lines; [ and ] pan"
+        });
+    }
+
     pub fn take_pending_preview(&mut self) -> Option<(String, PreviewMode)> {
         self.pending_preview.take()
     }
 
-    pub fn load_preview_after_unlock(&mut self, rel: String, mode: PreviewMode) -> Result<()> {
+    pub fn load_preview_after_unlock(&mut self, rel: String, mode: PreviewMode) {
         self.load_preview(rel, mode, true)
     }
 
     pub fn update_preview(&mut self) {
         // Determine selected entry path (only files have content)
-        let key = self.selected_entry_path();
+        let key = self.selected_preview_key();
         match key {
             Some(rel) => {
-                if self.preview_key.as_deref() != Some(&rel)
-                    || self.preview_mode != PreviewMode::Raw
+                if self.preview_key.as_deref() != Some(&rel) || self.preview_mode != self.text_mode
                 {
-                    if let Err(err) = self.load_preview(rel.clone(), PreviewMode::Raw, false) {
-                        self.status = Some(err.to_string());
-                    }
+                    self.load_preview(rel, self.text_mode, false);
                 }
             }
             None => {
@@ -428,9 +2646,65 @@ impl App {
                 self.preview_key = None;
                 self.preview_text.clear();
                 self.preview_is_error = false;
-                self.preview_mode = PreviewMode::Raw;
+                self.preview_mode = self.text_mode;
+                self.preview_scroll = 0;
+                self.preview_hscroll = 0;
                 self.pending_preview = None;
+                self.preview_loading = None;
+            }
+        }
+    }
+
+    /// With `preview_delay_ms` set, preview the selection once it has stayed put that long.
+    /// Returns whether a preview was started.
+    pub fn debounce_preview(&mut self) -> bool {
+        let Some(delay) = self.config.preview_delay_ms else {
+            return false;
+        };
+        let selected = self.selected_preview_key();
+        if selected.is_none() || selected == self.preview_key {
+            self.preview_pending_since = None;
+            self.preview_pending_key = None;
+            return false;
+        }
+        if selected != self.preview_pending_key {
+            // the cursor moved: restart the wait for the new selection
+            self.preview_pending_since = Some(Instant::now());
+            self.preview_pending_key = selected;
+            return false;
+        }
+        match self.preview_pending_since {
+            Some(since) if since.elapsed() >= Duration::from_millis(delay) => {
+                self.preview_pending_since = None;
+                self.preview_pending_key = None;
+                self.update_preview();
+                true
             }
+            _ => false,
+        }
+    }
+
+    /// Switch between the raw text and the field table, for this and later previews. An entry
+    /// already decrypted is shown the other way without decrypting it again.
+    pub fn toggle_fields(&mut self) {
+        self.text_mode = match self.text_mode {
+            PreviewMode::Fields => PreviewMode::Raw,
+            _ => PreviewMode::Fields,
+        };
+        if matches!(self.preview_mode, PreviewMode::Raw | PreviewMode::Fields) {
+            self.preview_mode = self.text_mode;
+        }
+        let label = match self.text_mode {
+            PreviewMode::Fields => "Preview shows fields",
+            _ => "Preview shows raw text",
+        };
+        self.set_status(label);
+    }
+
+    /// Show the entry's current one-time code. Always reloads since codes expire.
+    pub fn update_preview_otp(&mut self) {
+        if let Some(rel) = self.selected_entry_path() {
+            self.load_preview(rel, PreviewMode::Otp, false);
         }
     }
 
@@ -438,18 +2712,223 @@ impl App {
         let key = self.selected_entry_path();
         if let Some(rel) = key {
             if self.preview_key.as_deref() != Some(&rel) || self.preview_mode != PreviewMode::Qr {
-                if let Err(err) = self.load_preview(rel.clone(), PreviewMode::Qr, false) {
-                    self.status = Some(err.to_string());
-                }
+                self.load_preview(rel, PreviewMode::Qr, false);
             }
         }
     }
 }
 
-fn password_store_dir() -> PathBuf {
-    if let Ok(dir) = env::var("PASSWORD_STORE_DIR") {
-        return PathBuf::from(dir);
+/// The file an entry at `path` is stored in; the extension is appended, since entry names may
+/// contain dots of their own (`example.com`).
+fn encrypted_file(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// Keep a list width (in percent) where both the list and the preview stay usable.
+fn clamp_list_width(percent: u16) -> u16 {
+    percent.clamp(LIST_WIDTH_RANGE.0, LIST_WIDTH_RANGE.1)
+}
+
+/// Check a path typed for a new entry and tidy it up: surrounding whitespace and repeated
+/// slashes are dropped, while absolute paths, `.`/`..` components and a trailing `/` (which
+/// would name a directory) are rejected with a message for the user.
+pub fn normalize_entry_path(input: &str) -> std::result::Result<String, String> {
+    let path = input.trim();
+    if path.is_empty() {
+        return Err("Enter a name for the entry".to_string());
+    }
+    if path.starts_with('/') {
+        return Err("Use a path inside the store, without a leading '/'".to_string());
+    }
+    if path.ends_with('/') {
+        return Err("Add a name after the last '/'".to_string());
+    }
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    if components.iter().any(|c| *c == "." || *c == "..") {
+        return Err("'.' and '..' are not allowed in entry paths".to_string());
+    }
+    Ok(components.join("/"))
+}
+
+/// Case-insensitive subsequence match in the style of fzf. Returns a score (higher is better)
+/// and the char indices of `name` that matched, or `None` if `pattern` is not a subsequence.
+pub fn fuzzy_match(name: &str, pattern: &str) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut positions: Vec<usize> = Vec::new();
+    let mut score = 0i64;
+    let mut next = 0;
+    for wanted in pattern.chars() {
+        let wanted = wanted.to_lowercase().next().unwrap_or(wanted);
+        let pos = (next..chars.len())
+            .find(|&i| chars[i].to_lowercase().next().unwrap_or(chars[i]) == wanted)?;
+        score += 16;
+        match positions.last() {
+            // consecutive characters are worth more than scattered ones
+            Some(&prev) if prev + 1 == pos => score += 24,
+            Some(&prev) => score -= (pos - prev - 1).min(8) as i64,
+            None => score -= pos.min(8) as i64,
+        }
+        let boundary = pos == 0
+            || !chars[pos - 1].is_alphanumeric()
+            || (chars[pos - 1].is_lowercase() && chars[pos].is_uppercase());
+        if boundary {
+            score += 12;
+        }
+        positions.push(pos);
+        next = pos + 1;
+    }
+    Some((score, positions))
+}
+
+/// Cap `text` at `max_bytes` (on a char boundary) and append a notice; 0 means unlimited.
+/// Also returns whether anything was cut.
+fn truncate_preview(mut text: String, max_bytes: usize) -> (String, bool) {
+    if max_bytes == 0 || text.len() <= max_bytes {
+        return (text, false);
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str("\n… (truncated, press X for full/pager)");
+    (text, true)
+}
+
+fn open_store(store_dir: &Path, index_plain: bool) -> Result<(PathBuf, Vec<StoreEntry>)> {
+    if !store_dir.exists() {
+        anyhow::bail!(
+            "Password store not found: {}. Set PASSWORD_STORE_DIR or --store.",
+            store_dir.display()
+        );
+    }
+    // Resolve symlinks once; the index, path comparisons and pass (via PASSWORD_STORE_DIR)
+    // all use the real location, so paths from either side line up
+    let canonical_dir = store_dir.canonicalize()?;
+    let entries = build_store_index(&canonical_dir, index_plain)?;
+    Ok((canonical_dir, entries))
+}
+
+/// The entry template with `{{path}}` replaced by the new entry's path and `{{name}}` by its
+/// last component.
+fn fill_template(template: &str, entry: &str) -> String {
+    let name = entry.rsplit('/').next().unwrap_or(entry);
+    template
+        .replace("{{path}}", entry)
+        .replace("{{name}}", name)
+}
+
+/// `content` with its first line swapped for `line`; the other lines and the final newline stay.
+fn replace_first_line(content: &str, line: &str) -> String {
+    match content.split_once('\n') {
+        Some((_, rest)) => format!("{}\n{}", line, rest),
+        None => line.to_string(),
+    }
+}
+
+/// The pass backend for `store_dir`, set up from the profile and the rest of the config.
+fn profile_backend(store_dir: PathBuf, profile: &Profile, config: &Config) -> Arc<dyn Backend> {
+    let mut backend = PassCliBackend::new(Some(store_dir));
+    backend.program = profile.backend.clone().or_else(|| config.backend.clone());
+    backend.store_env = config.store_env.clone();
+    backend.editor = profile.editor.clone();
+    backend.clipboard = config.clipboard;
+    backend.password_label = config.password_label.clone();
+    backend.verbose = config.verbose;
+    backend.dry_run = config.dry_run;
+    Arc::new(backend)
+}
+
+/// The store the profile's backend program uses by default (`PASSWORD_STORE_DIR`, `PASSAGE_DIR`).
+fn password_store_dir(profile: &Profile, config: &Config) -> PathBuf {
+    let program = profile
+        .backend
+        .as_deref()
+        .or(config.backend.as_deref())
+        .unwrap_or("pass");
+    default_store_dir(
+        config
+            .store_env
+            .as_deref()
+            .unwrap_or_else(|| store_env_for(program)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_paths_are_normalized() {
+        assert_eq!(
+            normalize_entry_path("  web//github "),
+            Ok("web/github".to_string())
+        );
+        assert_eq!(normalize_entry_path("bank"), Ok("bank".to_string()));
+    }
+
+    #[test]
+    fn bad_entry_paths_are_rejected() {
+        for bad in [
+            "",
+            "   ",
+            "/etc/passwd",
+            "web/",
+            "../outside",
+            "web/../../x",
+            "./web",
+            "a/./b",
+        ] {
+            assert!(normalize_entry_path(bad).is_err(), "{bad:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn fuzzy_match_finds_subsequences_and_prefers_tight_matches() {
+        let (_, positions) = fuzzy_match("GitHub", "ghb").unwrap();
+        assert_eq!(positions, vec![0, 3, 5]);
+        assert!(fuzzy_match("GitHub", "hg").is_none());
+
+        let (tight, _) = fuzzy_match("github", "git").unwrap();
+        let (loose, _) = fuzzy_match("gmail-ident", "git").unwrap();
+        assert!(tight > loose);
+    }
+
+    #[test]
+    fn replacing_the_first_line_keeps_the_rest() {
+        assert_eq!(
+            replace_first_line("old\nlogin: alice\nurl: x\n", "new"),
+            "new\nlogin: alice\nurl: x\n"
+        );
+        assert_eq!(replace_first_line("old\n", "new"), "new\n");
+        assert_eq!(replace_first_line("old", "new"), "new");
+    }
+
+    #[test]
+    fn templates_name_the_new_entry() {
+        assert_eq!(
+            fill_template(
+                "\nlogin: {{name}}\nurl: https://{{name}}.example\n# {{path}}\n",
+                "web/github"
+            ),
+            "\nlogin: github\nurl: https://github.example\n# web/github\n"
+        );
+    }
+
+    #[test]
+    fn previews_are_cut_on_a_char_boundary() {
+        // 'é' takes bytes 1..3, so a cap of 2 would split it
+        assert_eq!(
+            truncate_preview("aé\nrest".into(), 2),
+            ("a\n… (truncated, press X for full/pager)".to_string(), true)
+        );
+        assert_eq!(truncate_preview("aé".into(), 3), ("aé".to_string(), false));
+        assert_eq!(
+            truncate_preview("long text".into(), 0),
+            ("long text".to_string(), false)
+        );
     }
-    let home = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
-    home.join(".password-store")
 }
@@ -0,0 +1,79 @@
+use crate::config::state_dir;
+use anyhow::{anyhow, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Append-only record of actions taken in the UI. Only action names and entry paths are
+/// written, never decrypted content.
+pub struct AuditLog {
+    pub path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn open_default() -> Result<Self> {
+        let dir = state_dir().ok_or_else(|| anyhow!("no state directory for audit log"))?;
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join("audit.log"),
+        })
+    }
+
+    pub fn record(&self, action: &str, entry: &str, outcome: &str) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(&self.path)?;
+        let line = format!(
+            "{}\t{}\t{}\t{}\n",
+            format_utc(SystemTime::now()),
+            action,
+            entry.replace(['\t', '\n'], " "),
+            outcome.replace(['\t', '\n'], " ")
+        );
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Format a time as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (hour, minute, second) = (rem / 3600, rem % 3600 / 60, rem % 60);
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(format_utc(leap_day), "2024-02-29T12:34:56Z");
+    }
+}
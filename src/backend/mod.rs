@@ -1,55 +1,314 @@
+use crate::clipboard::{self, Clipboard};
+use crate::fields;
 use anyhow::Result;
+use std::env;
 use std::fmt;
 use std::fs;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use zeroize::Zeroize;
 
-pub trait Backend: Send {
+pub trait Backend: Send + Sync {
     fn edit(&self, entry: &str) -> Result<()>;
     fn yank(&self, entry: &str) -> Result<()>;
     fn add(&self, entry: &str) -> Result<()> {
         self.edit(entry)
     }
     fn rm(&self, target: &str, recursive: bool) -> Result<()>;
+    /// Create an entry from `content` without an editor: every line with `multiline`,
+    /// otherwise just the first line as the password
+    fn insert(&self, entry: &str, content: &str, multiline: bool) -> Result<()>;
+    /// Overwrite an existing entry with `content`, every line kept
+    fn replace(&self, entry: &str, content: &str) -> Result<()>;
     fn show(&self, entry: &str) -> Result<String>;
-    fn show_qr(&self, entry: &str) -> Result<String>;
+    /// What the QR preview encodes: the entry's `otpauth://` URI if it has one, so an
+    /// authenticator app can scan it, otherwise its password
+    fn secret_for_qr(&self, entry: &str) -> Result<String> {
+        let mut content = self.show(entry)?;
+        let secret = fields::find_otpauth(&content)
+            .unwrap_or_else(|| fields::secret(&content, self.password_label()).0)
+            .to_string();
+        content.zeroize();
+        Ok(secret)
+    }
     fn mv(&self, from: &str, to: &str) -> Result<()>;
-    fn unlock(&self, _entry: &str, _qr: bool) -> Result<()> {
+    /// Duplicate an entry, or a directory with everything in it
+    fn cp(&self, from: &str, to: &str) -> Result<()>;
+    /// Key ids or emails an entry (or directory) is encrypted to, from the nearest `.gpg-id`
+    fn recipients(&self, entry: &str) -> Result<Vec<String>>;
+    /// Whether a new entry at `entry` has recipients to be encrypted to
+    fn has_recipients(&self, _entry: &str) -> bool {
+        true
+    }
+    /// Name of the files listing recipients per directory
+    fn recipients_file(&self) -> &'static str {
+        ".gpg-id"
+    }
+    /// Copy the value of a `field: value` line (e.g. `username`) and clear it like `pass -c`
+    fn yank_field(&self, entry: &str, field: &str) -> Result<()> {
+        let mut content = self.show(entry)?;
+        let result = match fields::find_field(&content, field) {
+            Some(value) => clipboard::copy_secret(self.clipboard(), value),
+            None => Err(anyhow::anyhow!("No '{}' field in {}", field, entry)),
+        };
+        content.zeroize();
+        result
+    }
+    /// Show the full decrypted entry in `$PAGER`; caller should suspend TUI before calling
+    fn page(&self, entry: &str) -> Result<()>;
+    /// Replace the password (first line) of an existing entry, keeping the remaining lines
+    fn generate_in_place(&self, entry: &str, length: usize, no_symbols: bool) -> Result<()>;
+    /// Current one-time code for an entry holding an `otpauth://` URI
+    fn otp(&self, _entry: &str) -> Result<String> {
+        anyhow::bail!("otp not supported")
+    }
+    /// Entries whose decrypted contents match `pattern`; caller should suspend TUI before calling
+    fn grep(&self, _pattern: &str) -> Result<Vec<String>> {
+        anyhow::bail!("grep not supported")
+    }
+    /// Run `git` in the store and return its output; caller should suspend TUI before calling
+    fn git(&self, _args: &[&str]) -> Result<String> {
+        anyhow::bail!("git not supported")
+    }
+    /// The latest `limit` commits touching an entry (or directory), newest first, one line each
+    fn git_log(&self, _entry: &str, _limit: usize) -> Result<Vec<String>> {
+        anyhow::bail!("git log not supported")
+    }
+    /// Whether the store's git repository has changes that are not committed
+    fn git_dirty(&self) -> Result<bool> {
+        anyhow::bail!("git not supported")
+    }
+    /// Decrypt `entry` with the terminal available, so gpg can ask for the passphrase; caller
+    /// should suspend TUI before calling
+    fn unlock(&self, _entry: &str) -> Result<()> {
+        Ok(())
+    }
+    /// Report a backend that cannot work at all (e.g. its program is not installed) up front
+    fn check(&self) -> Result<()> {
         Ok(())
     }
+    /// Report that `edit` has no editor to run before the caller suspends the TUI for it
+    fn check_editor(&self) -> Result<()> {
+        Ok(())
+    }
+    /// How `yank` and in-process copies reach the clipboard
+    fn clipboard(&self) -> Clipboard {
+        Clipboard::default()
+    }
+    /// Field holding the password when an entry has it on a labeled line; see `password_label`
+    fn password_label(&self) -> Option<&str> {
+        None
+    }
 }
 
 #[derive(Default, Clone)]
 pub struct PassCliBackend {
     pub store_dir: Option<PathBuf>,
+    /// Command to run instead of `pass`
+    pub program: Option<String>,
+    /// Editor exported as `EDITOR` for `pass edit`
+    pub editor: Option<String>,
+    /// Anything but `Pass` makes `yank` decrypt with `show` and pipe line 1 to a clipboard tool
+    pub clipboard: Clipboard,
+    /// Variable the program reads the store directory from; see [`store_env_for`]
+    pub store_env: Option<String>,
+    /// Label of the line holding the password, for the QR preview
+    pub password_label: Option<String>,
+    /// Print every command to stderr before running it
+    pub verbose: bool,
+    /// Print commands that change the store (rm, mv, generate, insert) instead of running them
+    pub dry_run: bool,
+    /// Where `verbose` and `dry_run` print commands; stderr when unset
+    pub command_log: Option<Arc<Mutex<dyn Write + Send>>>,
+}
+
+/// Whether `program` (a path, or a name looked up in `PATH`) exists to be run.
+fn is_runnable(program: &str) -> bool {
+    let program = Path::new(program);
+    if program.as_os_str().is_empty() {
+        false
+    } else if program.components().count() > 1 {
+        program.is_file()
+    } else {
+        env::var_os("PATH")
+            .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+    }
+}
+
+/// The store directory variable of `program`: `PASSAGE_DIR` for passage, `PASSWORD_STORE_DIR`
+/// for pass and anything else.
+pub fn store_env_for(program: &str) -> &'static str {
+    if Path::new(program)
+        .file_name()
+        .is_some_and(|name| name == "passage")
+    {
+        "PASSAGE_DIR"
+    } else {
+        "PASSWORD_STORE_DIR"
+    }
+}
+
+/// The store a program uses when none is given: `$<store_env>`, else its default under home.
+pub fn default_store_dir(store_env: &str) -> PathBuf {
+    if let Some(dir) = env::var_os(store_env) {
+        return PathBuf::from(dir);
+    }
+    let home = dirs_next::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+    if store_env == "PASSAGE_DIR" {
+        home.join(".passage/store")
+    } else {
+        home.join(".password-store")
+    }
 }
 
 impl PassCliBackend {
     pub fn new(store_dir: Option<PathBuf>) -> Self {
-        Self { store_dir }
+        Self {
+            store_dir,
+            ..Self::default()
+        }
+    }
+
+    fn program(&self) -> &str {
+        self.program.as_deref().unwrap_or("pass")
+    }
+
+    fn store_env(&self) -> &str {
+        self.store_env
+            .as_deref()
+            .unwrap_or_else(|| store_env_for(self.program()))
+    }
+
+    fn is_passage(&self) -> bool {
+        self.store_env() == "PASSAGE_DIR"
+    }
+
+    /// File of `entry` relative to the store, as git knows it: `.gpg`, or `.age` for passage.
+    fn entry_file(&self, entry: &str) -> String {
+        match resolve_source(&self.store_root(), entry) {
+            Ok((_, Some(ext))) => format!("{}.{}", entry, ext),
+            Ok((_, None)) => entry.to_string(),
+            // gone from disk, but git still has its history
+            Err(_) if self.is_passage() => format!("{}.age", entry),
+            Err(_) => format!("{}.gpg", entry),
+        }
+    }
+
+    /// Run `pass mv` or `pass cp` (`subcommand`), or apply `fallback` to the files when pass is
+    /// not installed.
+    fn relocate(
+        &self,
+        subcommand: &str,
+        from: &str,
+        to: &str,
+        fallback: fn(&Path, &Path) -> io::Result<()>,
+    ) -> Result<()> {
+        let store = self.store_root();
+        let (src, ext) = resolve_source(&store, from)?;
+        // Resolve "move into" ourselves so pass and the fallback below agree on the target
+        let to = move_destination(&store, from, to)?;
+        let dst = destination_path(&store, &to, ext);
+
+        let mut cmd = self.cmd();
+        cmd.arg(subcommand).arg(from).arg(&to);
+        if self.skip_in_dry_run(&cmd) {
+            return Ok(());
+        }
+        let output = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output();
+        match output {
+            Ok(output) => check_output(&output, &format!("pass {}", subcommand)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if let Some(parent) = dst.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fallback(&src, &dst)?;
+                Ok(())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Turn a failure to start the program into an error that says what is missing.
+    fn spawn_error(&self, err: io::Error) -> anyhow::Error {
+        if err.kind() != io::ErrorKind::NotFound {
+            return err.into();
+        }
+        match self.program {
+            None => anyhow::anyhow!("`pass` not found on PATH — is passwordstore.org installed?"),
+            Some(ref program) => anyhow::anyhow!("`{program}` not found on PATH"),
+        }
     }
 
     fn cmd(&self) -> Command {
-        let mut cmd = Command::new("pass");
+        let mut cmd = Command::new(self.program());
         if let Some(dir) = &self.store_dir {
-            cmd.env("PASSWORD_STORE_DIR", dir);
+            cmd.env(self.store_env(), dir);
         }
         cmd
     }
 
+    /// With `verbose`, print `cmd` as it is about to run.
+    fn log(&self, cmd: &Command) {
+        if self.verbose {
+            self.print_command("", cmd);
+        }
+    }
+
+    fn print_command(&self, tag: &str, cmd: &Command) {
+        let line = format!("pass-tui: {}{}", tag, describe_command(cmd));
+        match &self.command_log {
+            Some(sink) => {
+                let mut sink = sink.lock().unwrap_or_else(|err| err.into_inner());
+                let _ = writeln!(sink, "{}", line);
+            }
+            None => eprintln!("{}", line),
+        }
+    }
+
+    /// With `dry_run`, print `cmd`, which would change the store, and report that it must be
+    /// skipped; otherwise log it like any other command.
+    fn skip_in_dry_run(&self, cmd: &Command) -> bool {
+        if self.dry_run {
+            self.print_command("[dry-run] ", cmd);
+        } else {
+            self.log(cmd);
+        }
+        self.dry_run
+    }
+
+    /// The editor `pass edit` runs and where it was configured: the profile's, then `VISUAL`
+    /// and `EDITOR`, and otherwise `vi` like pass itself.
+    fn editor(&self) -> (String, Option<&'static str>) {
+        if let Some(editor) = &self.editor {
+            return (editor.clone(), Some("the profile"));
+        }
+        for var in ["VISUAL", "EDITOR"] {
+            if let Some(editor) = env::var(var).ok().filter(|value| !value.trim().is_empty()) {
+                return (editor, Some(var));
+            }
+        }
+        ("vi".to_string(), None)
+    }
+
     fn store_root(&self) -> PathBuf {
-        self.store_dir.clone().unwrap_or_else(|| {
-            dirs_next::home_dir()
-                .unwrap_or_default()
-                .join(".password-store")
-        })
+        self.store_dir
+            .clone()
+            .unwrap_or_else(|| default_store_dir(self.store_env()))
     }
 
     fn capture(&self, args: &[&str]) -> std::io::Result<std::process::Output> {
         let mut cmd = self.cmd();
         cmd.args(args);
         cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::null());
+        cmd.stderr(Stdio::piped());
+        self.log(&cmd);
         cmd.output()
     }
 
@@ -59,42 +318,366 @@ impl PassCliBackend {
         cmd.stdin(Stdio::inherit());
         cmd.stdout(Stdio::null());
         cmd.stderr(Stdio::inherit());
+        self.log(&cmd);
         cmd.status()
     }
 
+    /// Run `cmd` with stdout discarded, reporting the last line of stderr if it fails.
+    /// Callers log `cmd` themselves, most through `skip_in_dry_run`.
+    fn run_quiet(&self, cmd: &mut Command, context: &str) -> Result<()> {
+        let output = cmd
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|err| self.spawn_error(err))?;
+        check_output(&output, context)
+    }
+
+    /// `pass insert`, with `-f` to overwrite an existing entry without the prompt
+    fn insert_with(&self, entry: &str, content: &str, multiline: bool, force: bool) -> Result<()> {
+        let mut cmd = self.cmd();
+        cmd.arg("insert").arg(if multiline { "-m" } else { "-e" });
+        if force {
+            cmd.arg("-f");
+        }
+        cmd.arg(entry);
+        if self.skip_in_dry_run(&cmd) {
+            return Ok(());
+        }
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| self.spawn_error(err))?;
+        let mut input = if multiline {
+            content.to_string()
+        } else {
+            content.lines().next().unwrap_or_default().to_string()
+        };
+        // pass reads line by line, so an unterminated last line would be dropped
+        if !input.ends_with('\n') {
+            input.push('\n');
+        }
+        let written = child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(input.as_bytes());
+        input.zeroize();
+        let output = child.wait_with_output()?;
+        written?;
+        check_output(&output, "pass insert")
+    }
+
     fn capture_string(&self, args: &[&str], context: &'static str) -> Result<String> {
-        let output = self.capture(args)?;
+        let output = self.capture(args).map_err(|err| self.spawn_error(err))?;
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
-            Err(PassStatusError {
-                context,
-                status: output.status,
-            }
-            .into())
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(PassStatusError::with_stderr(context, output.status, &stderr).into())
+        }
+    }
+}
+
+/// `cmd` as a shell would read it, store variable first: `PASSWORD_STORE_DIR=/s pass rm -f x`.
+pub fn describe_command(cmd: &Command) -> String {
+    let quote = |word: &std::ffi::OsStr| {
+        let word = word.to_string_lossy();
+        if !word.is_empty()
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:@%+=,".contains(c))
+        {
+            word.into_owned()
+        } else {
+            format!("'{}'", word.replace('\'', "'\\''"))
         }
+    };
+    let mut words: Vec<String> = cmd
+        .get_envs()
+        .filter_map(|(key, value)| {
+            let value = value?;
+            Some(format!("{}={}", key.to_string_lossy(), quote(value)))
+        })
+        .collect();
+    words.push(quote(cmd.get_program()));
+    words.extend(cmd.get_args().map(quote));
+    words.join(" ")
+}
+
+fn check_output(output: &std::process::Output, context: &str) -> Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if let Some(err) = recipient_failure(context, output.status, &stderr) {
+        return Err(err);
+    }
+    match last_line(&stderr) {
+        Some(line) => anyhow::bail!("{context} failed: {line}"),
+        None => anyhow::bail!("{context} failed: {}", output.status),
     }
 }
 
-fn resolve_source(store: &Path, key: &str) -> Result<(PathBuf, bool)> {
+/// The directory or encrypted file behind `key`, with the file's extension: `gpg` for pass,
+/// `age` for passage. Directories come back without one.
+fn resolve_source(store: &Path, key: &str) -> Result<(PathBuf, Option<&'static str>)> {
     let dir = store.join(key);
     if dir.is_dir() {
-        return Ok((dir, true));
+        return Ok((dir, None));
     }
 
-    let file = store.join(format!("{}.gpg", key));
-    if file.is_file() {
-        return Ok((file, false));
+    for ext in ["gpg", "age"] {
+        let file = store.join(format!("{}.{}", key, ext));
+        if file.is_file() {
+            return Ok((file, Some(ext)));
+        }
     }
 
     anyhow::bail!("source not found: {}", key)
 }
 
-fn destination_path(store: &Path, key: &str, is_dir: bool) -> PathBuf {
-    if is_dir {
-        store.join(key)
+/// Run an interactive command, copying its stderr to ours while also collecting it.
+fn status_teeing_stderr(cmd: &mut Command) -> std::io::Result<(ExitStatus, String)> {
+    let mut child = cmd.stderr(Stdio::piped()).spawn()?;
+    let mut pipe = child.stderr.take().expect("stderr is piped");
+    let reader = thread::spawn(move || {
+        let mut collected = Vec::new();
+        let mut buf = [0u8; 1024];
+        let mut stderr = io::stderr();
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let _ = stderr.write_all(&buf[..n]);
+            let _ = stderr.flush();
+            collected.extend_from_slice(&buf[..n]);
+        }
+        collected
+    });
+    let status = child.wait()?;
+    let collected = reader.join().unwrap_or_default();
+    Ok((status, String::from_utf8_lossy(&collected).into_owned()))
+}
+
+/// The `.gpg-id` that governs `entry`: the closest one walking up from its directory to the
+/// store root, since pass lets subdirectories override the recipients of the root.
+pub fn find_gpg_id(store: &Path, entry: &str) -> Option<PathBuf> {
+    find_recipients_file(store, entry, ".gpg-id")
+}
+
+/// The closest file called `name` (`.gpg-id`, or `.age-recipients` for passage) walking up
+/// from the directory of `entry` to the store root.
+pub fn find_recipients_file(store: &Path, entry: &str, name: &str) -> Option<PathBuf> {
+    let target = store.join(entry);
+    let mut dir = if target.is_dir() {
+        target.as_path()
     } else {
-        store.join(format!("{}.gpg", key))
+        target.parent()?
+    };
+    loop {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if dir == store {
+            return None;
+        }
+        dir = dir.parent().filter(|parent| parent.starts_with(store))?;
+    }
+}
+
+/// One recipient per line; blank lines and `#` comments are skipped.
+pub fn parse_gpg_id(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Entry names from `pass grep` output. Each matching entry gets a header line printed as
+/// `ESC[94m<dir/>ESC[1m<name>ESC[0m:` followed by the matching lines, which never start blue.
+pub fn parse_grep_output(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with("\x1b[94m"))
+        .map(|line| {
+            let plain = strip_ansi(line);
+            plain.strip_suffix(':').unwrap_or(&plain).to_string()
+        })
+        .collect()
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip a CSI sequence: ESC [ params final-byte
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Last non-blank line of command output, which for git is usually the summary or the error.
+pub fn last_line(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).rfind(|line| !line.is_empty())
+}
+
+/// Extract gpg complaints about unusable recipients (missing, expired or revoked keys).
+pub fn recipient_problems(stderr: &str) -> Vec<String> {
+    const MARKERS: [&str; 5] = [
+        "no public key",
+        "unusable public key",
+        "skipped:",
+        "expired",
+        "revoked",
+    ];
+    stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            MARKERS.iter().any(|marker| lower.contains(marker))
+        })
+        .map(|line| line.trim_start_matches("gpg:").trim().to_string())
+        .collect()
+}
+
+/// An error naming the unusable recipients if gpg complained about any, since the last line of
+/// its output ("encryption failed") does not say which key is the problem.
+fn recipient_failure(context: &str, status: ExitStatus, stderr: &str) -> Option<anyhow::Error> {
+    let problems = recipient_problems(stderr);
+    (!problems.is_empty()).then(|| {
+        anyhow::anyhow!(
+            "{context} failed: {status} (recipients: {})",
+            problems.join("; ")
+        )
+    })
+}
+
+/// Whether `pass edit` exited 1 because the entry was left as it was, not because it failed.
+fn edit_unchanged(stderr: &str) -> bool {
+    stderr.lines().any(|line| {
+        let line = line.trim();
+        line.ends_with("Password unchanged.") || line.ends_with("New password not saved.")
+    })
+}
+
+/// The store key `from` ends up at when moved to `to`. Like pass, moving onto an existing
+/// directory moves into it and keeps the basename, creating no intermediate directories of its
+/// own. Refuses to overwrite: an entry `x.gpg` and a directory `x/` both show up as `x`, so
+/// either one counts as taken.
+pub fn move_destination(store: &Path, from: &str, to: &str) -> Result<String> {
+    let to = to.trim_end_matches('/');
+    let target = if store.join(to).is_dir() {
+        let name = Path::new(from)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if to.is_empty() {
+            name
+        } else {
+            format!("{}/{}", to, name)
+        }
+    } else {
+        to.to_string()
+    };
+    if target.starts_with(&format!("{}/", from)) {
+        anyhow::bail!("cannot put '{}' inside itself", from);
+    }
+    let taken = |ext| store.join(format!("{}.{}", target, ext)).exists();
+    if store.join(&target).exists() || taken("gpg") || taken("age") {
+        anyhow::bail!("destination exists: {}", target);
+    }
+    Ok(target)
+}
+
+fn copy_recursively(src: &Path, dst: &Path) -> io::Result<()> {
+    if !src.is_dir() {
+        return fs::copy(src, dst).map(|_| ());
+    }
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        copy_recursively(&entry.path(), &dst.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+fn destination_path(store: &Path, key: &str, ext: Option<&str>) -> PathBuf {
+    match ext {
+        Some(ext) => store.join(format!("{}.{}", key, ext)),
+        None => store.join(key),
+    }
+}
+
+/// Why gpg could not decrypt an entry, as far as its stderr tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpgFailure {
+    /// The passphrase could not be asked for (no terminal, pinentry cancelled)
+    Locked,
+    /// None of the entry's recipients is a secret key in the keyring
+    NoSecretKey,
+    /// The file is not valid OpenPGP data or failed to decrypt
+    Corrupt,
+    /// The entry does not exist
+    NotInStore,
+}
+
+impl GpgFailure {
+    pub fn reason(self) -> &'static str {
+        match self {
+            GpgFailure::Locked => "GPG key locked",
+            GpgFailure::NoSecretKey => {
+                "No secret key for this entry; it was encrypted for a key not in your keyring"
+            }
+            GpgFailure::Corrupt => "Decryption failed; the entry file looks damaged",
+            GpgFailure::NotInStore => "Entry is not in the password store",
+        }
+    }
+}
+
+/// Classify the stderr of a failed `pass show`.
+pub fn classify_gpg_failure(stderr: &str) -> Option<GpgFailure> {
+    let stderr = stderr.to_lowercase();
+    // checked first: a locked key also makes gpg report "decryption failed: No secret key"
+    const LOCKED: [&str; 4] = [
+        "inappropriate ioctl",
+        "pinentry",
+        "operation cancelled",
+        "no passphrase given",
+    ];
+    if LOCKED.iter().any(|marker| stderr.contains(marker)) {
+        Some(GpgFailure::Locked)
+    } else if stderr.contains("no secret key") {
+        Some(GpgFailure::NoSecretKey)
+    } else if [
+        "decryption failed",
+        "no valid openpgp data",
+        "invalid packet",
+    ]
+    .iter()
+    .any(|marker| stderr.contains(marker))
+    {
+        Some(GpgFailure::Corrupt)
+    } else if stderr.contains("is not in the password store") {
+        Some(GpgFailure::NotInStore)
+    } else {
+        None
     }
 }
 
@@ -102,11 +685,45 @@ fn destination_path(store: &Path, key: &str, is_dir: bool) -> PathBuf {
 pub struct PassStatusError {
     pub context: &'static str,
     pub status: ExitStatus,
+    pub failure: Option<GpgFailure>,
+    /// Last line of stderr, when it was captured
+    pub detail: Option<String>,
+}
+
+impl PassStatusError {
+    pub fn new(context: &'static str, status: ExitStatus) -> Self {
+        Self {
+            context,
+            status,
+            failure: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_stderr(context: &'static str, status: ExitStatus, stderr: &str) -> Self {
+        Self {
+            context,
+            status,
+            failure: classify_gpg_failure(stderr),
+            detail: last_line(stderr).map(str::to_string),
+        }
+    }
+
+    /// Whether prompting for the passphrase may fix the failure.
+    pub fn needs_unlock(&self) -> bool {
+        self.status.code() == Some(2) && matches!(self.failure, None | Some(GpgFailure::Locked))
+    }
 }
 
 impl fmt::Display for PassStatusError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} failed: {}", self.context, self.status)
+        match (self.failure, &self.detail) {
+            (Some(failure), _) if failure != GpgFailure::Locked => {
+                write!(f, "{}", failure.reason())
+            }
+            (_, Some(detail)) => write!(f, "{} failed: {}", self.context, detail),
+            _ => write!(f, "{} failed: {}", self.context, self.status),
+        }
     }
 }
 
@@ -115,31 +732,42 @@ impl std::error::Error for PassStatusError {}
 impl Backend for PassCliBackend {
     fn edit(&self, entry: &str) -> Result<()> {
         // interactive; caller should suspend TUI before calling
-        let status = self.cmd().arg("edit").arg(entry).status()?;
+        self.check_editor()?;
+        let mut cmd = self.cmd();
+        cmd.env("EDITOR", self.editor().0).arg("edit").arg(entry);
+        self.log(&cmd);
+        let (status, stderr) =
+            status_teeing_stderr(&mut cmd).map_err(|err| self.spawn_error(err))?;
         if status.success() {
             return Ok(());
         }
-        // pass edit returns exit code 1 when nothing changed; treat that as success
-        if status.code() == Some(1) {
+        if let Some(err) = recipient_failure("pass edit", status, &stderr) {
+            return Err(err);
+        }
+        // pass edit also exits 1 when nothing changed (or nothing was saved); only that is fine,
+        // "Password encryption aborted" and other failures exit 1 too
+        if status.code() == Some(1) && edit_unchanged(&stderr) {
             return Ok(());
         }
-        anyhow::bail!("pass edit failed: {status}")
+        match last_line(&stderr) {
+            Some(line) => anyhow::bail!("pass edit failed: {line}"),
+            None => anyhow::bail!("pass edit failed: {status}"),
+        }
     }
 
     fn yank(&self, entry: &str) -> Result<()> {
-        // suppress pass output in TUI
-        let status = self
-            .cmd()
-            .arg("-c")
-            .arg(entry)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()?;
-        if status.success() {
-            Ok(())
-        } else {
-            anyhow::bail!("pass -c failed: {status}")
+        if self.clipboard != Clipboard::Pass {
+            let mut content = self.show(entry)?;
+            let result =
+                clipboard::copy_secret(self.clipboard, content.lines().next().unwrap_or(""));
+            content.zeroize();
+            return result;
         }
+        // suppress pass output in TUI
+        let mut cmd = self.cmd();
+        cmd.arg("-c").arg(entry);
+        self.log(&cmd);
+        self.run_quiet(&mut cmd, "pass -c")
     }
 
     fn rm(&self, target: &str, recursive: bool) -> Result<()> {
@@ -149,16 +777,19 @@ impl Backend for PassCliBackend {
             cmd.arg("-r");
         }
         cmd.arg("-f"); // confirm in TUI, force in pass
-        let status = cmd
-            .arg(target)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()?;
-        if status.success() {
-            Ok(())
-        } else {
-            anyhow::bail!("pass rm failed: {status}")
+        cmd.arg(target);
+        if self.skip_in_dry_run(&cmd) {
+            return Ok(());
         }
+        self.run_quiet(&mut cmd, "pass rm")
+    }
+
+    fn insert(&self, entry: &str, content: &str, multiline: bool) -> Result<()> {
+        self.insert_with(entry, content, multiline, false)
+    }
+
+    fn replace(&self, entry: &str, content: &str) -> Result<()> {
+        self.insert_with(entry, content, true, true)
     }
 
     fn show(&self, entry: &str) -> Result<String> {
@@ -167,39 +798,249 @@ impl Backend for PassCliBackend {
         self.capture_string(&args, "pass show")
     }
 
-    fn show_qr(&self, entry: &str) -> Result<String> {
-        // Use `pass show -q <entry>` to produce QR (if supported by user's pass setup)
-        let args = ["show", "-q", entry];
-        self.capture_string(&args, "pass show -q")
+    fn mv(&self, from: &str, to: &str) -> Result<()> {
+        // pass mv re-encrypts for the destination's .gpg-id and commits to git
+        self.relocate("mv", from, to, |src, dst| fs::rename(src, dst))
     }
 
-    fn mv(&self, from: &str, to: &str) -> Result<()> {
-        let store = self.store_root();
-        let (src, is_dir) = resolve_source(&store, from)?;
-        let dst = destination_path(&store, to, is_dir);
+    fn cp(&self, from: &str, to: &str) -> Result<()> {
+        // pass cp copies recursively, re-encrypts for the destination's .gpg-id and commits
+        self.relocate("cp", from, to, copy_recursively)
+    }
 
-        if let Some(parent) = dst.parent() {
-            fs::create_dir_all(parent)?;
+    fn recipients(&self, entry: &str) -> Result<Vec<String>> {
+        let name = self.recipients_file();
+        let file = find_recipients_file(&self.store_root(), entry, name);
+        match file {
+            Some(file) => Ok(parse_gpg_id(&fs::read_to_string(file)?)),
+            // passage then encrypts to the keys of its identities file
+            None if self.is_passage() => anyhow::bail!(
+                "No {} found for {}; passage encrypts to its identities",
+                name,
+                entry
+            ),
+            None => anyhow::bail!("No {} found for {}", name, entry),
         }
-        // Prevent overwriting existing destination
-        if dst.exists() {
-            anyhow::bail!("destination exists: {}", to);
+    }
+
+    fn has_recipients(&self, entry: &str) -> bool {
+        // passage falls back to its identities file
+        self.is_passage() || find_gpg_id(&self.store_root(), entry).is_some()
+    }
+
+    fn recipients_file(&self) -> &'static str {
+        if self.is_passage() {
+            ".age-recipients"
+        } else {
+            ".gpg-id"
+        }
+    }
+
+    fn page(&self, entry: &str) -> Result<()> {
+        let mut show = self.cmd();
+        show.arg("show").arg(entry);
+        self.log(&show);
+        let mut show = show
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|err| self.spawn_error(err))?;
+        let stdout = show.stdout.take().expect("stdout is piped");
+        let pager = env::var("PAGER")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| "less".to_string());
+        // PAGER may carry arguments (e.g. "less -R"), so let the shell split it
+        let pager_status = Command::new("sh")
+            .arg("-c")
+            .arg(&pager)
+            .stdin(stdout)
+            .status()?;
+        let show_status = show.wait()?;
+        if !show_status.success() {
+            return Err(PassStatusError::new("pass show", show_status).into());
+        }
+        if !pager_status.success() {
+            anyhow::bail!("{pager} failed: {pager_status}");
         }
-        fs::rename(&src, &dst)?;
         Ok(())
     }
 
-    fn unlock(&self, entry: &str, qr: bool) -> Result<()> {
-        let (args, context): (Vec<&str>, &str) = if qr {
-            (vec!["show", "-q", entry], "pass show -q")
-        } else {
-            (vec![entry], "pass show")
-        };
-        let status = self.status_interactive(&args)?;
+    fn generate_in_place(&self, entry: &str, length: usize, no_symbols: bool) -> Result<()> {
+        let mut cmd = self.cmd();
+        cmd.arg("generate").arg("-i");
+        if no_symbols {
+            cmd.arg("-n");
+        }
+        cmd.arg(entry).arg(length.to_string());
+        if self.skip_in_dry_run(&cmd) {
+            return Ok(());
+        }
+        self.run_quiet(&mut cmd, "pass generate")
+    }
+
+    fn otp(&self, entry: &str) -> Result<String> {
+        let mut cmd = self.cmd();
+        cmd.arg("otp").arg(entry);
+        self.log(&cmd);
+        let output = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|err| self.spawn_error(err))?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        // exit code 2 means gpg could not decrypt; keep the status so the caller can unlock
+        if stderr.is_empty() || output.status.code() == Some(2) {
+            return Err(PassStatusError::with_stderr("pass otp", output.status, stderr).into());
+        }
+        // e.g. "No OTP key found" or "otp is not in the password store" from pass-otp
+        anyhow::bail!("pass otp: {stderr}")
+    }
+
+    fn grep(&self, pattern: &str) -> Result<Vec<String>> {
+        let mut cmd = self.cmd();
+        cmd.arg("grep").arg(pattern);
+        self.log(&cmd);
+        let output = cmd
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|err| self.spawn_error(err))?;
+        // grep exits 1 when nothing matched, which pass passes through
+        if !output.status.success() && output.status.code() != Some(1) {
+            return Err(PassStatusError::new("pass grep", output.status).into());
+        }
+        Ok(parse_grep_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String> {
+        // stdin stays on the terminal; ssh and git prompt via /dev/tty, so capturing is safe
+        let mut cmd = self.cmd();
+        cmd.arg("git").args(args);
+        self.log(&cmd);
+        let output = cmd
+            .stdin(Stdio::inherit())
+            .output()
+            .map_err(|err| self.spawn_error(err))?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        if output.status.success() {
+            return Ok(text);
+        }
+        match last_line(&text) {
+            Some(line) => anyhow::bail!("pass git {} failed: {}", args.join(" "), line),
+            None => anyhow::bail!("pass git {} failed: {}", args.join(" "), output.status),
+        }
+    }
+
+    fn git_log(&self, entry: &str, limit: usize) -> Result<Vec<String>> {
+        let store = self.store_root();
+        if !store.join(".git").exists() {
+            anyhow::bail!("not a git store");
+        }
+        let path = self.entry_file(entry);
+        let limit = limit.to_string();
+        let args = ["git", "log", "--oneline", "-n", &limit, "--", &path];
+        let output = self.capture_string(&args, "pass git log")?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn git_dirty(&self) -> Result<bool> {
+        if !self.store_root().join(".git").exists() {
+            return Ok(false);
+        }
+        let output = self.capture_string(&["git", "status", "--porcelain"], "pass git status")?;
+        Ok(!output.trim().is_empty())
+    }
+
+    fn unlock(&self, entry: &str) -> Result<()> {
+        // the same plain `pass <entry>` every preview, QR included, decrypts with
+        let status = self
+            .status_interactive(&[entry])
+            .map_err(|err| self.spawn_error(err))?;
         if status.success() {
             Ok(())
         } else {
-            Err(PassStatusError { context, status }.into())
+            Err(PassStatusError::new("pass show", status).into())
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        if is_runnable(self.program()) {
+            Ok(())
+        } else {
+            Err(self.spawn_error(io::ErrorKind::NotFound.into()))
         }
     }
+
+    fn check_editor(&self) -> Result<()> {
+        let (editor, from) = self.editor();
+        let program = editor.split_whitespace().next().unwrap_or_default();
+        if is_runnable(program) {
+            return Ok(());
+        }
+        match from {
+            None => anyhow::bail!("No editor found: set EDITOR or VISUAL (`vi` is not installed)"),
+            Some(from) => anyhow::bail!("Editor `{program}` from {from} not found"),
+        }
+    }
+
+    fn clipboard(&self) -> Clipboard {
+        self.clipboard
+    }
+
+    fn password_label(&self) -> Option<&str> {
+        self.password_label.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_gpg_id_wins() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let store = tmp.path();
+        fs::create_dir_all(store.join("team/db"))?;
+        fs::write(store.join(".gpg-id"), "me@example.com\n")?;
+        fs::write(
+            store.join("team/.gpg-id"),
+            "# shared\nalice@example.com\n\n0xDEADBEEF  # bob\n",
+        )?;
+        fs::write(store.join("team/db/prod.gpg"), "x")?;
+        fs::write(store.join("mail.gpg"), "x")?;
+
+        let backend = PassCliBackend::new(Some(store.to_path_buf()));
+        assert_eq!(
+            backend.recipients("team/db/prod")?,
+            vec!["alice@example.com", "0xDEADBEEF"]
+        );
+        assert_eq!(
+            backend.recipients("team")?,
+            vec!["alice@example.com", "0xDEADBEEF"]
+        );
+        assert_eq!(backend.recipients("mail")?, vec!["me@example.com"]);
+        Ok(())
+    }
+
+    #[test]
+    fn grep_output_yields_entry_names_only() {
+        let output = "\x1b[94mweb/\x1b[1mgithub\x1b[0m:\n\
+                      url: https://\x1b[01;31m\x1b[Kgithub\x1b[m\x1b[K.com\n\
+                      \x1b[94m\x1b[1mbank\x1b[0m:\n\
+                      note: see github: later\n";
+        assert_eq!(parse_grep_output(output), vec!["web/github", "bank"]);
+    }
 }
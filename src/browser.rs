@@ -0,0 +1,26 @@
+use anyhow::{anyhow, Result};
+use std::io::ErrorKind;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Open `url` in the default browser: `open` on macOS, `xdg-open` elsewhere.
+pub fn open(url: &str) -> Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let mut child = Command::new(program)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => anyhow!("`{program}` not found on PATH"),
+            _ => err.into(),
+        })?;
+    // reap it in the background; the browser may take a while to hand the url over
+    thread::spawn(move || child.wait());
+    Ok(())
+}
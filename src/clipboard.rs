@@ -0,0 +1,138 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::env;
+use std::io::{ErrorKind, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+type Tool = (&'static str, &'static [&'static str]);
+
+const WL_COPY: Tool = ("wl-copy", &[]);
+const XCLIP: Tool = ("xclip", &["-selection", "clipboard"]);
+const XSEL: Tool = ("xsel", &["--clipboard", "--input"]);
+const PBCOPY: Tool = ("pbcopy", &[]);
+
+/// The shell that will clear the last secret copied by [`copy_secret`]
+static PENDING_CLEAR: Mutex<Option<Child>> = Mutex::new(None);
+
+/// How secrets reach the clipboard: through `pass -c`, or piped to a clipboard tool by pass-tui.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Clipboard {
+    /// `pass -c`; copies pass-tui makes itself (fields, labeled passwords) pick a tool like `auto`
+    #[default]
+    Pass,
+    WlCopy,
+    Xclip,
+    Xsel,
+    /// wl-copy under Wayland (`WAYLAND_DISPLAY`), xclip or xsel under X11 (`DISPLAY`)
+    Auto,
+}
+
+/// Clipboard tools in order of preference: program and arguments that read the text from stdin.
+fn candidates(clipboard: Clipboard) -> Vec<Tool> {
+    match clipboard {
+        Clipboard::WlCopy => vec![WL_COPY],
+        Clipboard::Xclip => vec![XCLIP],
+        Clipboard::Xsel => vec![XSEL],
+        Clipboard::Pass | Clipboard::Auto => {
+            let mut tools = Vec::new();
+            if env::var_os("WAYLAND_DISPLAY").is_some() {
+                tools.push(WL_COPY);
+            }
+            if env::var_os("DISPLAY").is_some() {
+                tools.extend([XCLIP, XSEL]);
+            }
+            tools.push(PBCOPY);
+            tools
+        }
+    }
+}
+
+/// Copy `text` to the system clipboard using the first tool available on PATH.
+pub fn copy(clipboard: Clipboard, text: &str) -> Result<()> {
+    copy_with_any(clipboard, text).map(|_| ())
+}
+
+/// Copy a secret and clear the clipboard after `PASSWORD_STORE_CLIP_TIME` seconds, like `pass -c`.
+/// The clear of an earlier secret is called off, so each copy gets the full time.
+pub fn copy_secret(clipboard: Clipboard, text: &str) -> Result<()> {
+    let (program, args) = copy_with_any(clipboard, text)?;
+    cancel_pending_clear();
+    // A detached shell outlives pass-tui, so quitting right after a copy still clears it
+    let script = format!("sleep {} && printf '' | \"$0\" \"$@\"", clip_time());
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .arg(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    *PENDING_CLEAR.lock().unwrap_or_else(|err| err.into_inner()) = Some(child);
+    Ok(())
+}
+
+/// Call off the clear scheduled by the last [`copy_secret`], before something else (such as
+/// `pass -c`, which clears on its own) takes over the clipboard.
+pub fn cancel_pending_clear() {
+    let pending = PENDING_CLEAR
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .take();
+    if let Some(mut child) = pending {
+        // the shell may be done already; either way it is reaped here
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Seconds before a copied secret is cleared, honoring `PASSWORD_STORE_CLIP_TIME`.
+pub fn clip_time() -> u64 {
+    env::var("PASSWORD_STORE_CLIP_TIME")
+        .ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .unwrap_or(45)
+}
+
+fn copy_with_any(clipboard: Clipboard, text: &str) -> Result<Tool> {
+    let tools = candidates(clipboard);
+    for &(program, args) in &tools {
+        let spawned = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        // wl-copy and xclip fork to serve the selection, so stderr is only read once it failed
+        let status = child.wait()?;
+        if status.success() {
+            return Ok((program, args));
+        }
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            pipe.read_to_string(&mut stderr)?;
+        }
+        match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(line) => anyhow::bail!("{program} failed: {}", line.trim()),
+            None => anyhow::bail!("{program} failed: {status}"),
+        }
+    }
+    match tools.as_slice() {
+        [(program, _)] if clipboard != Clipboard::Auto && clipboard != Clipboard::Pass => {
+            Err(anyhow!("`{program}` not found on PATH"))
+        }
+        _ => Err(anyhow!(
+            "no clipboard tool found (install wl-clipboard, xclip or xsel)"
+        )),
+    }
+}
@@ -0,0 +1,246 @@
+use crate::clipboard::Clipboard;
+use crate::keymap::KeySpec;
+use crate::theme::{Preset, Theme};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// After deleting a directory, offer to remove parents left empty by the deletion
+    pub prune_empty_dirs: bool,
+    /// Largest decrypted entry (in KiB) rendered in the preview pane; 0 disables the cap
+    pub preview_max_kb: usize,
+    /// Length of passwords produced by the regenerate action
+    pub generate_length: usize,
+    /// Generate passwords without symbols (`pass generate -n`)
+    pub generate_no_symbols: bool,
+    /// What Enter does on a password entry
+    pub enter_action: EnterAction,
+    /// Preview the selected entry once the cursor has rested this many milliseconds; unset
+    /// previews only on Enter
+    pub preview_delay_ms: Option<u64>,
+    /// Fixed preview pane width in columns; the list takes the rest. Unset splits 50/50
+    pub preview_width: Option<u16>,
+    /// Share of the screen width given to the list, 20 to 80; wins over `preview_width`
+    pub list_width_percent: Option<u16>,
+    /// Draw in the terminal's alternate screen; when false the UI is drawn inline
+    pub alt_screen: bool,
+    /// Draw with the terminal's default colors only, ignoring any theme (also `--no-color`
+    /// and the `NO_COLOR` environment variable)
+    pub no_color: bool,
+    /// Draw the tree and icons with ASCII only (also `--ascii`); unset picks ASCII on the Linux
+    /// console and in locales that are not UTF-8
+    pub ascii: Option<bool>,
+    /// Seconds a flashed password stays on screen
+    pub flash_seconds: u64,
+    /// Append performed actions (never secrets) to `$XDG_STATE_HOME/pass-tui/audit.log`
+    pub audit_log: bool,
+    /// Number the rows of the list, as targets for `:`
+    pub line_numbers: bool,
+    /// `j` on the last row moves to the first and `k` on the first to the last
+    pub wrap_around: bool,
+    /// `l` on a directory makes it the list root instead of expanding it; `h` at the top goes up
+    pub cd_into_dirs: bool,
+    /// Also list files that are not `.gpg` entries, previewed as plain text
+    pub index_plain: bool,
+    /// Start with the filter that was applied when pass-tui last quit (`--no-persist-filter`
+    /// turns this off and forgets the saved filter)
+    pub persist_filter: bool,
+    /// Keep the filters recalled with Up/Down in the session file too; off by default since
+    /// they can name entries
+    pub persist_filter_history: bool,
+    /// Refresh the list when entries are added, removed or renamed by other programs; off by
+    /// default since the store is polled
+    pub watch_store: bool,
+    /// Milliseconds between the walks of the store made by `watch_store`
+    pub watch_interval_ms: u64,
+    /// Warn at startup about gpg keys named in `.gpg-id` files that expire within this many
+    /// days (or already have); 0 skips the check
+    pub key_expiry_warn_days: u64,
+    /// Contents for entries started with `add_template`; `{{name}}` and `{{path}}` are replaced.
+    /// Defaults to `template` next to the config file
+    pub template: Option<PathBuf>,
+    /// Treat the value of this `label:` line as the password when an entry has one
+    pub password_label: Option<String>,
+    /// How yanked passwords reach the clipboard (also `--clipboard`)
+    pub clipboard: Clipboard,
+    /// Fields copied one per press by `copy_sequence`: `username` for the account line,
+    /// `password` for the secret, or any other field name
+    pub copy_sequence: Vec<String>,
+    /// Command run in place of `pass` (e.g. `passage`) unless the profile names one; also
+    /// `--backend-cmd`
+    pub backend: Option<String>,
+    /// Variable the backend reads its store directory from; by default `PASSAGE_DIR` for
+    /// passage and `PASSWORD_STORE_DIR` otherwise
+    pub store_env: Option<String>,
+    /// Set by `--verbose`; not read from the file
+    #[serde(skip)]
+    pub verbose: bool,
+    /// Set by `--dry-run`; not read from the file
+    #[serde(skip)]
+    pub dry_run: bool,
+    pub theme: Theme,
+    /// Key bindings by action name, replacing that action's default keys
+    pub keys: BTreeMap<String, KeySpec>,
+    /// Named bundles of settings selected with `--profile` or switched at runtime
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Password store directory (`~` is expanded)
+    pub store: Option<PathBuf>,
+    /// Command run in place of `pass`
+    pub backend: Option<String>,
+    /// Editor for `pass edit`, overriding `EDITOR`
+    pub editor: Option<String>,
+    /// Directory within the store that the list opens in
+    pub cwd: Option<PathBuf>,
+    /// Replaces the top-level theme while the profile is active
+    pub theme: Option<Theme>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnterAction {
+    #[default]
+    #[serde(alias = "view")]
+    Preview,
+    #[serde(alias = "copy")]
+    Yank,
+    ViewAndYank,
+}
+
+impl EnterAction {
+    /// What Enter does, as shown in the header help line
+    pub fn help(self) -> &'static str {
+        match self {
+            EnterAction::Preview => "view",
+            EnterAction::Yank => "copy",
+            EnterAction::ViewAndYank => "view+copy",
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            prune_empty_dirs: false,
+            preview_max_kb: 64,
+            generate_length: 25,
+            generate_no_symbols: false,
+            enter_action: EnterAction::Preview,
+            preview_delay_ms: None,
+            preview_width: None,
+            list_width_percent: None,
+            alt_screen: true,
+            no_color: false,
+            ascii: None,
+            flash_seconds: 5,
+            audit_log: false,
+            line_numbers: false,
+            wrap_around: false,
+            cd_into_dirs: false,
+            index_plain: false,
+            persist_filter: true,
+            persist_filter_history: false,
+            watch_store: false,
+            watch_interval_ms: 500,
+            key_expiry_warn_days: 30,
+            template: None,
+            password_label: None,
+            clipboard: Clipboard::default(),
+            copy_sequence: vec!["username".into(), "password".into()],
+            backend: None,
+            store_env: None,
+            verbose: false,
+            dry_run: false,
+            theme: Theme::default(),
+            keys: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults when it does not exist.
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Invalid config {}", path.display()))
+    }
+}
+
+impl Config {
+    /// Where the entry template is read from: `template`, or `template` in the config directory.
+    pub fn template_path(&self) -> Option<PathBuf> {
+        match &self.template {
+            Some(path) => Some(expand_tilde(path)),
+            None => config_dir().map(|dir| dir.join("template")),
+        }
+    }
+
+    /// The theme to draw with: the profile's, else the top-level one, unless colors are off.
+    pub fn theme_for(&self, profile: &Profile) -> Theme {
+        if self.no_color {
+            return Theme::preset(Preset::Nocolor);
+        }
+        profile.theme.clone().unwrap_or_else(|| self.theme.clone())
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                anyhow!("Unknown profile '{}': no profiles configured", name)
+            } else {
+                anyhow!(
+                    "Unknown profile '{}'. Available: {}",
+                    name,
+                    known.join(", ")
+                )
+            }
+        })
+    }
+}
+
+/// Expand a leading `~` to the home directory.
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => dirs_next::home_dir().unwrap_or_default().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs_next::config_dir(),
+    }
+    .map(|dir| dir.join("pass-tui"))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Directory for runtime state such as logs: `$XDG_STATE_HOME/pass-tui` or `~/.local/state/pass-tui`.
+pub fn state_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs_next::home_dir().map(|home| home.join(".local/state")),
+    }
+    .map(|dir| dir.join("pass-tui"))
+}
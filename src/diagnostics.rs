@@ -0,0 +1,151 @@
+use crate::backend::Backend;
+use crate::store::{build_store_index, EntryKind};
+use std::env;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zeroize::Zeroize;
+
+/// Environment variables that may carry secrets; only report whether they are set.
+const OPAQUE_VARS: [&str; 1] = ["PASSWORD_STORE_GPG_OPTS"];
+
+/// Collect configuration facts useful for bug reports. Never decrypts anything.
+pub fn collect(store_dir: &Path, entry_count: usize) -> Vec<(String, String)> {
+    let mut facts = vec![
+        ("Store".to_string(), store_dir.display().to_string()),
+        ("Entries".to_string(), entry_count.to_string()),
+        ("pass".to_string(), probe_version("pass", "version")),
+        ("gpg".to_string(), probe_version("gpg", "--version")),
+        ("Git".to_string(), git_status(store_dir)),
+    ];
+
+    let mut vars: Vec<(String, String)> = env::vars()
+        .filter(|(name, _)| name.starts_with("PASSWORD_STORE_"))
+        .map(|(name, value)| {
+            if OPAQUE_VARS.contains(&name.as_str()) {
+                (name, "(set)".to_string())
+            } else {
+                (name, value)
+            }
+        })
+        .collect();
+    vars.sort();
+    if vars.is_empty() {
+        facts.push(("PASSWORD_STORE_*".to_string(), "(none set)".to_string()));
+    }
+    facts.extend(vars);
+    facts
+}
+
+/// One line of `pass-tui doctor`: what was checked and either what was found or what is wrong.
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Result<String, String>,
+}
+
+/// Check that the store can be browsed and decrypted, outside the TUI. Decrypts one entry
+/// (discarding its contents), so gpg may ask for the passphrase.
+pub fn doctor(store_dir: &Path, backend: &dyn Backend) -> Vec<Check> {
+    let mut checks = vec![Check {
+        name: "pass on PATH",
+        outcome: backend
+            .check()
+            .map(|()| "found".to_string())
+            .map_err(|err| err.to_string()),
+    }];
+    if !store_dir.is_dir() {
+        checks.push(Check {
+            name: "store directory",
+            outcome: Err(format!("{} does not exist", store_dir.display())),
+        });
+        return checks;
+    }
+    checks.push(Check {
+        name: "store directory",
+        outcome: Ok(store_dir.display().to_string()),
+    });
+    let recipients_file = backend.recipients_file();
+    checks.push(Check {
+        name: recipients_file,
+        outcome: if store_dir.join(recipients_file).is_file() {
+            Ok("present".to_string())
+        } else if recipients_file == ".age-recipients" {
+            // not required: passage encrypts to its identities file without one
+            Ok("missing; encrypting to the identities file".to_string())
+        } else {
+            Err("missing; run `pass init <gpg-id>`".to_string())
+        },
+    });
+    let first_entry = build_store_index(store_dir, false)
+        .ok()
+        .and_then(|entries| {
+            entries
+                .into_iter()
+                .find(|entry| entry.kind == EntryKind::Entry)
+        });
+    checks.push(Check {
+        name: "decrypt an entry",
+        outcome: match first_entry {
+            Some(entry) => {
+                let key = entry.store_key();
+                match backend.show(&key) {
+                    Ok(mut content) => {
+                        content.zeroize();
+                        Ok(format!("decrypted {key}"))
+                    }
+                    Err(err) => Err(format!("{key}: {err}")),
+                }
+            }
+            None => Err("the store has no entries to try".to_string()),
+        },
+    });
+    checks
+}
+
+fn probe_version(program: &str, arg: &str) -> String {
+    let output = Command::new(program)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let text = String::from_utf8_lossy(&output.stdout);
+            find_version(&text).unwrap_or_else(|| "installed (unknown version)".to_string())
+        }
+        Ok(output) => format!("error ({})", output.status),
+        Err(_) => "not found on PATH".to_string(),
+    }
+}
+
+/// Pick the first token that looks like a version number, e.g. `v1.7.4` or `2.4.5`.
+fn find_version(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .map(|token| token.trim_start_matches('v'))
+        .find(|token| token.contains('.') && token.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        .map(str::to_string)
+}
+
+fn git_status(store_dir: &Path) -> String {
+    if !store_dir.join(".git").exists() {
+        return "not a git repository".to_string();
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(store_dir)
+        .args(["status", "--porcelain"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let changes = String::from_utf8_lossy(&output.stdout).lines().count();
+            if changes == 0 {
+                "clean".to_string()
+            } else {
+                format!("{} uncommitted changes", changes)
+            }
+        }
+        Ok(output) => format!("git status failed: {}", output.status),
+        Err(_) => "git not found on PATH".to_string(),
+    }
+}
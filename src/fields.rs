@@ -0,0 +1,152 @@
+/// Split a `key: value` line, trimming both sides. Lines without a key are not fields.
+pub fn parse_field(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim();
+    // a bare URL is not a field named "https"
+    if key.is_empty() || key.contains(char::is_whitespace) || value.starts_with("//") {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+/// Value of the first `name: value` line (case-insensitive on `name`), skipping line 1.
+pub fn find_field<'a>(content: &'a str, name: &str) -> Option<&'a str> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(parse_field)
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Split an entry in the usual multiline format into its password (line 1) and the lines
+/// after it as `(key, value)` pairs. Lines that are not fields (notes, bare URLs) keep an empty
+/// key; blank lines are dropped.
+pub fn parse_entry(content: &str) -> (&str, Vec<(&str, &str)>) {
+    let mut lines = content.lines();
+    let password = lines.next().unwrap_or_default();
+    let fields = lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_field(line).unwrap_or(("", line.trim())))
+        .collect();
+    (password, fields)
+}
+
+/// Field names that conventionally hold the account name, in no particular order
+const USERNAME_FIELDS: [&str; 4] = ["login", "user", "username", "email"];
+
+/// The first line after the password that names the account (`login:`, `user:`, ...), as
+/// field name and value.
+pub fn find_username(content: &str) -> Option<(&str, &str)> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(parse_field)
+        .find(|(key, _)| {
+            USERNAME_FIELDS
+                .iter()
+                .any(|name| key.eq_ignore_ascii_case(name))
+        })
+}
+
+/// The address to open for an entry: its `url:` line, or else the first `http(s)://` word after
+/// the password.
+pub fn find_url(content: &str) -> Option<&str> {
+    find_field(content, "url").or_else(|| {
+        content
+            .lines()
+            .skip(1)
+            .flat_map(str::split_whitespace)
+            .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+    })
+}
+
+/// The `otpauth://` URI of an entry, on a line of its own (as pass-otp writes it) or as the
+/// value of a field such as `otp:`.
+pub fn find_otpauth(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .map(|line| parse_field(line).map_or(line, |(_, value)| value).trim())
+        .find(|value| value.starts_with("otpauth://"))
+}
+
+/// The secret of an entry: the value of the `label:` line when `label` is set and present,
+/// otherwise the first line. The flag reports whether the label was used.
+pub fn secret<'a>(content: &'a str, label: Option<&str>) -> (&'a str, bool) {
+    let labeled = label.and_then(|label| {
+        content
+            .lines()
+            .filter_map(parse_field)
+            .find(|(key, _)| key.eq_ignore_ascii_case(label))
+            .map(|(_, value)| value)
+    });
+    match labeled {
+        Some(value) => (value, true),
+        None => (content.lines().next().unwrap_or_default(), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labeled_password_wins_over_first_line() {
+        let content = "url: https://example.com\nuser: alice\nPassword:  hunter2 \n";
+        assert_eq!(secret(content, Some("password")), ("hunter2", true));
+        assert_eq!(secret(content, None), ("url: https://example.com", false));
+        assert_eq!(
+            secret("s3cret\nuser: bob\n", Some("password")),
+            ("s3cret", false)
+        );
+    }
+
+    #[test]
+    fn url_field_wins_over_bare_links() {
+        let content =
+            "https://not-the-password.example\nsee http://docs.example/x\nURL: example.com\n";
+        assert_eq!(find_url(content), Some("example.com"));
+        let content = "s3cret\nnotes: login at https://example.com/login today\n";
+        assert_eq!(find_url(content), Some("https://example.com/login"));
+        assert_eq!(find_url("https://only-line-1.example\n"), None);
+    }
+
+    #[test]
+    fn otpauth_uri_is_found_bare_or_as_a_field() {
+        let uri = "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP";
+        assert_eq!(find_otpauth(&format!("s3cret\n{uri}\n")), Some(uri));
+        assert_eq!(find_otpauth(&format!("s3cret\notp: {uri}\n")), Some(uri));
+        assert_eq!(find_otpauth("s3cret\nurl: https://example.com\n"), None);
+    }
+
+    #[test]
+    fn username_is_the_first_account_field() {
+        let content = "s3cret\nurl: https://example.com\nEmail: a@example.com\nlogin: alice\n";
+        assert_eq!(find_username(content), Some(("Email", "a@example.com")));
+        assert_eq!(find_username("login: not-a-field-on-line-1\n"), None);
+    }
+
+    #[test]
+    fn entries_split_into_password_and_fields() {
+        let content = "s3cret\nuser: bob\n\nhttps://example.com\nurl: https://example.com\n";
+        let (password, fields) = parse_entry(content);
+        assert_eq!(password, "s3cret");
+        assert_eq!(
+            fields,
+            vec![
+                ("user", "bob"),
+                ("", "https://example.com"),
+                ("url", "https://example.com"),
+            ]
+        );
+        assert_eq!(parse_entry(""), ("", vec![]));
+    }
+
+    #[test]
+    fn fields_are_case_insensitive_and_trimmed() {
+        let content = "s3cret\nUserName :  bob \nnot a field: x\n";
+        assert_eq!(find_field(content, "username"), Some("bob"));
+        assert_eq!(find_field(content, "not a field"), None);
+        assert_eq!(parse_field("https://example.com"), None);
+    }
+}
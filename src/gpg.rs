@@ -0,0 +1,228 @@
+//! Expiry of the gpg keys a store is encrypted to, so they can be extended or rotated before
+//! the entries become undecryptable.
+
+use crate::backend::parse_gpg_id;
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+const DAY_SECS: u64 = 86_400;
+
+/// A primary key from `gpg --with-colons --list-keys`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub key_id: String,
+    /// The first user id, e.g. `Alice <alice@example.com>`
+    pub user_id: Option<String>,
+    /// Expiry in seconds since the epoch, the earlier of the primary key's and its encryption
+    /// subkeys'; `None` if none of them expire
+    pub expires: Option<u64>,
+}
+
+impl Key {
+    fn name(&self) -> &str {
+        self.user_id.as_deref().unwrap_or(&self.key_id)
+    }
+}
+
+/// Recipients of every `.gpg-id` in the store, the root's and those of subdirectories.
+pub fn store_recipients(store: &Path) -> Vec<String> {
+    let recipients: BTreeSet<String> = WalkDir::new(store)
+        .follow_links(true)
+        .into_iter()
+        // .git and other hidden directories hold no recipients
+        .filter_entry(|entry| entry.depth() == 0 || !is_hidden_dir(entry))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && entry.file_name() == ".gpg-id")
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .flat_map(|text| parse_gpg_id(&text))
+        .collect();
+    recipients.into_iter().collect()
+}
+
+fn is_hidden_dir(entry: &walkdir::DirEntry) -> bool {
+    entry.file_type().is_dir() && entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Look up `recipients` in the local keyring. Recipients gpg does not know are left out.
+pub fn list_keys(recipients: &[String]) -> Result<Vec<Key>> {
+    if recipients.is_empty() {
+        return Ok(Vec::new());
+    }
+    // gpg exits non-zero when any recipient is missing but still lists the others
+    let output = Command::new("gpg")
+        .args(["--batch", "--with-colons", "--list-keys", "--"])
+        .args(recipients)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()?;
+    Ok(parse_keys(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Primary keys of a `--with-colons` listing, each listed once.
+pub fn parse_keys(listing: &str) -> Vec<Key> {
+    let mut keys: Vec<Key> = Vec::new();
+    for line in listing.lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        let field = |n: usize| fields.get(n - 1).copied().unwrap_or_default();
+        match field(1) {
+            "pub" => keys.push(Key {
+                key_id: field(5).to_string(),
+                user_id: None,
+                expires: field(7).parse().ok(),
+            }),
+            // pass encrypts to the subkey, so its expiry counts as much as the primary's
+            "sub" if field(12).contains('e') => {
+                if let Some(key) = keys.last_mut() {
+                    if let Ok(expires) = field(7).parse::<u64>() {
+                        key.expires = Some(key.expires.map_or(expires, |own| own.min(expires)));
+                    }
+                }
+            }
+            "uid" => {
+                if let Some(key) = keys.last_mut().filter(|key| key.user_id.is_none()) {
+                    key.user_id = Some(unescape(field(10)));
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut seen = BTreeSet::new();
+    keys.retain(|key| seen.insert(key.key_id.clone()));
+    keys
+}
+
+/// gpg escapes `:` and other special bytes in user ids as `\x3a`.
+fn unescape(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .strip_prefix(b"x")
+            .filter(|_| byte == b'\\')
+            .and_then(|hex| hex.get(..2))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[3..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A warning naming the keys that have expired or expire within `days` of `now` (seconds
+/// since the epoch), or `None` when all are fine.
+pub fn expiry_warning(keys: &[Key], now: u64, days: u64) -> Option<String> {
+    let horizon = now.saturating_add(days.saturating_mul(DAY_SECS));
+    let findings: Vec<String> = keys
+        .iter()
+        .filter_map(|key| {
+            let expires = key.expires.filter(|&expires| expires <= horizon)?;
+            Some(if expires <= now {
+                format!(
+                    "{} expired {} days ago",
+                    key.name(),
+                    (now - expires) / DAY_SECS
+                )
+            } else {
+                format!(
+                    "{} expires in {} days",
+                    key.name(),
+                    (expires - now) / DAY_SECS
+                )
+            })
+        })
+        .collect();
+    match findings.len() {
+        0 => None,
+        1 => Some(format!("GPG key {}", findings[0])),
+        _ => Some(format!("GPG keys: {}", findings.join("; "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "\
+tru::1:1700000000:0:3:1:5
+pub:u:255:22:AAAA1111AAAA1111:1600000000:1800000000::u:::scESC:::::ed25519:::0:
+fpr:::::::::0123456789ABCDEF0123AAAA1111AAAA1111:
+uid:u::::1600000000::HASH::Alice \\x3aWork\\x3a <alice@example.com>::::::::::0:
+uid:u::::1600000000::HASH::Alice <alice@home.example>::::::::::0:
+sub:u:255:18:BBBB2222BBBB2222:1600000000:1800000000:::::e:::::cv25519::
+pub:u:255:22:CCCC3333CCCC3333:1600000000:::u:::scESC:::::ed25519:::0:
+uid:u::::1600000000::HASH::Bob <bob@example.com>::::::::::0:
+sub:u:255:22:DDDD4444DDDD4444:1600000000:1700000000:::::s:::::ed25519::
+sub:u:255:18:EEEE5555EEEE5555:1600000000:1800432000:::::e:::::cv25519::
+";
+
+    #[test]
+    fn primary_keys_are_parsed_with_their_first_user_id() {
+        let keys = parse_keys(LISTING);
+        assert_eq!(
+            keys,
+            vec![
+                Key {
+                    key_id: "AAAA1111AAAA1111".into(),
+                    user_id: Some("Alice :Work: <alice@example.com>".into()),
+                    expires: Some(1_800_000_000),
+                },
+                Key {
+                    key_id: "CCCC3333CCCC3333".into(),
+                    user_id: Some("Bob <bob@example.com>".into()),
+                    // the encryption subkey's; the signing subkey does not count
+                    expires: Some(1_800_432_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn only_keys_expiring_soon_are_reported() {
+        let keys = parse_keys(LISTING);
+        let expires = 1_800_000_000;
+        assert_eq!(expiry_warning(&keys, expires - 40 * DAY_SECS, 30), None);
+        assert_eq!(
+            expiry_warning(&keys, expires - 12 * DAY_SECS, 10).as_deref(),
+            None
+        );
+        assert_eq!(
+            expiry_warning(&keys, expires - 12 * DAY_SECS, 15).as_deref(),
+            Some("GPG key Alice :Work: <alice@example.com> expires in 12 days")
+        );
+        // Bob's primary key never expires, but the subkey pass encrypts to does
+        assert_eq!(
+            expiry_warning(&keys, expires + 3 * DAY_SECS, 30).as_deref(),
+            Some(
+                "GPG keys: Alice :Work: <alice@example.com> expired 3 days ago; \
+                 Bob <bob@example.com> expires in 2 days"
+            )
+        );
+    }
+
+    #[test]
+    fn recipients_are_collected_from_every_gpg_id() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        fs::create_dir_all(tmp.path().join("team"))?;
+        fs::write(tmp.path().join(".gpg-id"), "me@example.com\n")?;
+        fs::write(
+            tmp.path().join("team/.gpg-id"),
+            "me@example.com\n# the team\nteam@example.com\n",
+        )?;
+        assert_eq!(
+            store_recipients(tmp.path()),
+            vec!["me@example.com".to_string(), "team@example.com".to_string()]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,514 @@
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Something a key in the list view can do; names are the keys of the `[keys]` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Down,
+    Up,
+    First,
+    Last,
+    HalfPageDown,
+    HalfPageUp,
+    View,
+    Collapse,
+    Expand,
+    ExpandAll,
+    CollapseAll,
+    Sort,
+    Flat,
+    Jump,
+    Goto,
+    Filter,
+    ClearFilter,
+    Grep,
+    PreviewDown,
+    PreviewUp,
+    PreviewLeft,
+    PreviewRight,
+    Wrap,
+    ShrinkList,
+    GrowList,
+    Reveal,
+    Qr,
+    Otp,
+    Fields,
+    Yank,
+    YankField,
+    YankUsername,
+    CopySequence,
+    CopyAll,
+    CopyKey,
+    CopyPath,
+    Flash,
+    Peek,
+    Pager,
+    OpenUrl,
+    Select,
+    Edit,
+    EditLine,
+    Add,
+    Insert,
+    AddTemplate,
+    Rename,
+    BulkRename,
+    Copy,
+    Delete,
+    Undo,
+    Regenerate,
+    Pull,
+    Push,
+    Profiles,
+    Stores,
+    Recipients,
+    GitLog,
+    Messages,
+    Diagnostics,
+}
+
+impl Action {
+    pub const ALL: [Action; 61] = [
+        Action::Quit,
+        Action::Down,
+        Action::Up,
+        Action::First,
+        Action::Last,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::View,
+        Action::Collapse,
+        Action::Expand,
+        Action::ExpandAll,
+        Action::CollapseAll,
+        Action::Sort,
+        Action::Flat,
+        Action::Jump,
+        Action::Goto,
+        Action::Filter,
+        Action::ClearFilter,
+        Action::Grep,
+        Action::PreviewDown,
+        Action::PreviewUp,
+        Action::PreviewLeft,
+        Action::PreviewRight,
+        Action::Wrap,
+        Action::ShrinkList,
+        Action::GrowList,
+        Action::Reveal,
+        Action::Qr,
+        Action::Otp,
+        Action::Fields,
+        Action::Yank,
+        Action::YankField,
+        Action::YankUsername,
+        Action::CopySequence,
+        Action::CopyAll,
+        Action::CopyKey,
+        Action::CopyPath,
+        Action::Flash,
+        Action::Peek,
+        Action::Pager,
+        Action::OpenUrl,
+        Action::Select,
+        Action::Edit,
+        Action::EditLine,
+        Action::Add,
+        Action::Insert,
+        Action::AddTemplate,
+        Action::Rename,
+        Action::BulkRename,
+        Action::Copy,
+        Action::Delete,
+        Action::Undo,
+        Action::Regenerate,
+        Action::Pull,
+        Action::Push,
+        Action::Profiles,
+        Action::Stores,
+        Action::Recipients,
+        Action::GitLog,
+        Action::Messages,
+        Action::Diagnostics,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::View => "view",
+            Action::Collapse => "collapse",
+            Action::Expand => "expand",
+            Action::ExpandAll => "expand_all",
+            Action::CollapseAll => "collapse_all",
+            Action::Sort => "sort",
+            Action::Flat => "flat",
+            Action::Jump => "jump",
+            Action::Goto => "goto",
+            Action::Filter => "filter",
+            Action::ClearFilter => "clear_filter",
+            Action::Grep => "grep",
+            Action::PreviewDown => "preview_down",
+            Action::PreviewUp => "preview_up",
+            Action::PreviewLeft => "preview_left",
+            Action::PreviewRight => "preview_right",
+            Action::Wrap => "wrap",
+            Action::ShrinkList => "shrink_list",
+            Action::GrowList => "grow_list",
+            Action::Reveal => "reveal",
+            Action::Qr => "qr",
+            Action::Otp => "otp",
+            Action::Fields => "fields",
+            Action::Yank => "yank",
+            Action::YankField => "yank_field",
+            Action::YankUsername => "yank_username",
+            Action::CopySequence => "copy_sequence",
+            Action::CopyAll => "copy_all",
+            Action::CopyKey => "copy_key",
+            Action::CopyPath => "copy_path",
+            Action::Flash => "flash",
+            Action::Peek => "peek",
+            Action::Select => "select",
+            Action::Pager => "pager",
+            Action::OpenUrl => "open_url",
+            Action::Edit => "edit",
+            Action::EditLine => "edit_line",
+            Action::Add => "add",
+            Action::Insert => "insert",
+            Action::AddTemplate => "add_template",
+            Action::Rename => "rename",
+            Action::BulkRename => "bulk_rename",
+            Action::Copy => "copy",
+            Action::Delete => "delete",
+            Action::Undo => "undo",
+            Action::Regenerate => "regenerate",
+            Action::Pull => "pull",
+            Action::Push => "push",
+            Action::Profiles => "profiles",
+            Action::Stores => "stores",
+            Action::Recipients => "recipients",
+            Action::GitLog => "git_log",
+            Action::Messages => "messages",
+            Action::Diagnostics => "diagnostics",
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "Q"],
+            Action::Down => &["j", "down"],
+            Action::Up => &["k", "up"],
+            Action::First => &["g", "home"],
+            Action::Last => &["G", "end"],
+            Action::HalfPageDown => &["ctrl-d"],
+            Action::HalfPageUp => &["ctrl-u"],
+            Action::View => &["enter"],
+            Action::Collapse => &["h", "left", "backspace"],
+            Action::Expand => &["l", "right"],
+            Action::ExpandAll => &["z R"],
+            Action::CollapseAll => &["z M"],
+            Action::Sort => &["S"],
+            Action::Flat => &["t"],
+            Action::Jump => &["f"],
+            Action::Goto => &[":"],
+            Action::Filter => &["/"],
+            Action::ClearFilter => &["esc"],
+            Action::Grep => &["ctrl-f"],
+            Action::PreviewDown => &["pagedown"],
+            Action::PreviewUp => &["pageup"],
+            Action::PreviewLeft => &["["],
+            Action::PreviewRight => &["]"],
+            Action::Wrap => &["w"],
+            Action::ShrinkList => &["<"],
+            Action::GrowList => &[">"],
+            Action::Reveal => &["v"],
+            Action::Qr => &["c", "C"],
+            Action::Otp => &["o"],
+            Action::Fields => &["tab"],
+            Action::Yank => &["y"],
+            Action::YankField => &["Y"],
+            Action::YankUsername => &["U"],
+            Action::CopySequence => &["T"],
+            Action::CopyAll => &["ctrl-a"],
+            Action::CopyKey => &["ctrl-y"],
+            Action::CopyPath => &["ctrl-g"],
+            Action::Flash => &["F"],
+            Action::Peek => &["K"],
+            Action::Select => &["space"],
+            Action::Pager => &["X"],
+            Action::OpenUrl => &["O"],
+            Action::Edit => &["e", "E"],
+            Action::EditLine => &["ctrl-e"],
+            Action::Add => &["a", "A"],
+            Action::Insert => &["I"],
+            Action::AddTemplate => &["ctrl-n"],
+            Action::Rename => &["r", "R"],
+            Action::BulkRename => &["M"],
+            Action::Copy => &["ctrl-c"],
+            Action::Delete => &["d", "D"],
+            Action::Undo => &["u"],
+            Action::Regenerate => &["N"],
+            Action::Pull => &["p"],
+            Action::Push => &["P"],
+            Action::Profiles => &["ctrl-p"],
+            Action::Stores => &["s"],
+            Action::Recipients => &["i"],
+            Action::GitLog => &["L"],
+            Action::Messages => &["m"],
+            Action::Diagnostics => &["?"],
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// One key or a list of keys bound to an action, e.g. `down = ["t", "down"]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn keys(&self) -> &[String] {
+        match self {
+            KeySpec::One(key) => std::slice::from_ref(key),
+            KeySpec::Many(keys) => keys,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Chord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Chord {
+    fn from_event(key: KeyEvent) -> Self {
+        let mut modifiers = key.modifiers;
+        // the case of a character already says whether shift was held
+        if matches!(key.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: key.code,
+            modifiers,
+        }
+    }
+}
+
+/// Parse a key sequence: chords separated by spaces, e.g. `z R` (z, then R).
+fn parse_sequence(spec: &str) -> Result<Vec<Chord>> {
+    let chords = spec
+        .split_whitespace()
+        .map(parse_chord)
+        .collect::<Result<Vec<_>>>()?;
+    if chords.is_empty() {
+        bail!("empty key");
+    }
+    Ok(chords)
+}
+
+/// Parse a chord such as `j`, `Q`, `?`, `ctrl-p`, `alt-enter` or `pagedown`.
+fn parse_chord(spec: &str) -> Result<Chord> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = spec;
+    // a lone "-" is a key, not a separator
+    while let Some((prefix, tail)) = rest.split_once('-').filter(|(_, tail)| !tail.is_empty()) {
+        modifiers |= match prefix.to_ascii_lowercase().as_str() {
+            "ctrl" | "c" => KeyModifiers::CONTROL,
+            "alt" | "a" | "m" => KeyModifiers::ALT,
+            _ => break,
+        };
+        rest = tail;
+    }
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => bail!("unknown key '{}'", spec),
+        },
+    };
+    Ok(Chord { code, modifiers })
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Vec<Chord>, Action>,
+    /// Keys typed so far of a multi-key sequence such as `z R`
+    pending: Vec<Chord>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&BTreeMap::new()).expect("default key bindings parse")
+    }
+}
+
+impl Keymap {
+    /// Start from the default bindings; an action listed in `overrides` loses its default keys.
+    pub fn from_config(overrides: &BTreeMap<String, KeySpec>) -> Result<Self> {
+        let mut custom: HashMap<Action, &[String]> = HashMap::new();
+        for (name, spec) in overrides {
+            let action = Action::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = Action::ALL.iter().map(|a| a.name()).collect();
+                anyhow!(
+                    "Unknown action '{}' in [keys]. Available: {}",
+                    name,
+                    known.join(", ")
+                )
+            })?;
+            custom.insert(action, spec.keys());
+        }
+
+        let mut bindings = HashMap::new();
+        for action in Action::ALL {
+            if custom.contains_key(&action) {
+                continue;
+            }
+            for key in action.default_keys() {
+                bindings.insert(parse_sequence(key)?, action);
+            }
+        }
+        // custom keys take precedence over any default they collide with
+        for (action, keys) in custom {
+            for key in keys {
+                let sequence = parse_sequence(key)
+                    .map_err(|err| anyhow!("[keys] {}: {}", action.name(), err))?;
+                bindings.insert(sequence, action);
+            }
+        }
+        Ok(Self {
+            bindings,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Feed a key press; returns the action once a binding is complete. A key that cannot
+    /// continue the pending sequence starts over on its own.
+    pub fn feed(&mut self, key: KeyEvent) -> Option<Action> {
+        let chord = Chord::from_event(key);
+        self.pending.push(chord);
+        if let Some(&action) = self.bindings.get(&self.pending) {
+            self.pending.clear();
+            return Some(action);
+        }
+        if self.is_prefix(&self.pending) {
+            return None;
+        }
+        self.pending.clear();
+        self.pending.push(chord);
+        if let Some(&action) = self.bindings.get(&self.pending) {
+            self.pending.clear();
+            return Some(action);
+        }
+        if !self.is_prefix(&self.pending) {
+            self.pending.clear();
+        }
+        None
+    }
+
+    fn is_prefix(&self, keys: &[Chord]) -> bool {
+        self.bindings
+            .keys()
+            .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_parse_modifiers() {
+        let overrides: BTreeMap<String, KeySpec> = toml::from_str(
+            r#"
+            down = ["t", "down"]
+            up = "n"
+            quit = "ctrl-q"
+            "#,
+        )
+        .unwrap();
+        let mut keymap = Keymap::from_config(&overrides).unwrap();
+
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('t'), none)),
+            Some(Action::Down)
+        );
+        assert_eq!(keymap.feed(press(KeyCode::Char('j'), none)), None);
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('n'), none)),
+            Some(Action::Up)
+        );
+        assert_eq!(keymap.feed(press(KeyCode::Char('q'), none)), None);
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        // shifted letters arrive with SHIFT set
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('Y'), KeyModifiers::SHIFT)),
+            Some(Action::YankField)
+        );
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('l'), none)),
+            Some(Action::Expand)
+        );
+    }
+
+    #[test]
+    fn sequences_wait_for_their_last_key() {
+        let mut keymap = Keymap::default();
+        let none = KeyModifiers::NONE;
+        assert_eq!(keymap.feed(press(KeyCode::Char('z'), none)), None);
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('R'), KeyModifiers::SHIFT)),
+            Some(Action::ExpandAll)
+        );
+        // a key that does not continue the sequence is handled on its own
+        assert_eq!(keymap.feed(press(KeyCode::Char('z'), none)), None);
+        assert_eq!(
+            keymap.feed(press(KeyCode::Char('j'), none)),
+            Some(Action::Down)
+        );
+    }
+
+    #[test]
+    fn unknown_actions_and_keys_are_errors() {
+        let bad_action: BTreeMap<String, KeySpec> = toml::from_str(r#"teleport = "x""#).unwrap();
+        assert!(Keymap::from_config(&bad_action).is_err());
+        let bad_key: BTreeMap<String, KeySpec> = toml::from_str(r#"down = "hyper-x""#).unwrap();
+        assert!(Keymap::from_config(&bad_key).is_err());
+    }
+}
@@ -1,4 +1,20 @@
+//! The model and pass backend behind the `pass-tui` binary. [`app::App`] holds the store index
+//! and the list state and can be driven without a terminal, e.g. from tests.
+
 pub mod app;
+pub mod audit;
 pub mod backend;
+pub mod browser;
+pub mod clipboard;
+pub mod config;
+pub mod diagnostics;
+pub mod fields;
+pub mod gpg;
+pub mod keymap;
+pub mod lock;
+pub mod session;
 pub mod store;
+pub mod strength;
+pub mod theme;
 pub mod ui;
+pub mod watch;
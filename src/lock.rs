@@ -0,0 +1,127 @@
+use crate::config::state_dir;
+use anyhow::{anyhow, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+/// Locks older than this are assumed to be left over from a crashed instance.
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Advisory lock held while an action modifies the store; released on drop.
+#[derive(Debug)]
+pub struct StoreLock {
+    path: PathBuf,
+}
+
+impl StoreLock {
+    /// Take the lock for `store_dir`, failing with a description of the holder if another
+    /// instance has it.
+    pub fn acquire(store_dir: &Path) -> Result<Self> {
+        let dir = state_dir().ok_or_else(|| anyhow!("no state directory for lock file"))?;
+        Self::acquire_in(&dir, store_dir)
+    }
+
+    /// Like [`StoreLock::acquire`], keeping the lock file in `lock_dir`.
+    pub fn acquire_in(lock_dir: &Path, store_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(lock_dir)?;
+        let path = lock_dir.join(format!("store-{:016x}.lock", fnv1a(store_dir)));
+
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", process::id())?;
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    if is_stale(&path) {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    let holder = fs::read_to_string(&path).unwrap_or_default();
+                    return Err(anyhow!(
+                        "store is locked by another pass-tui (pid {})",
+                        holder.trim()
+                    ));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Err(anyhow!("could not lock store"))
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn is_stale(path: &Path) -> bool {
+    let pid = fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.trim().parse::<u32>().ok());
+    // Where /proc exists we can tell whether the holder is still running, however long it
+    // has held the lock (a long `pass edit`); the age is only a guess for elsewhere
+    if let Some(pid) = pid {
+        let proc_dir = Path::new("/proc");
+        if proc_dir.is_dir() {
+            return !proc_dir.join(pid.to_string()).exists();
+        }
+    }
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > STALE_AFTER)
+}
+
+/// Stable hash so every build maps a store to the same lock file.
+fn fnv1a(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn second_acquire_fails_until_released() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let store = Path::new("/tmp/some-store");
+
+        let lock = StoreLock::acquire_in(tmp.path(), store)?;
+        let err = StoreLock::acquire_in(tmp.path(), store).unwrap_err();
+        assert!(err.to_string().contains(&process::id().to_string()));
+        drop(lock);
+        assert!(StoreLock::acquire_in(tmp.path(), store).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn old_locks_of_running_instances_are_kept() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let store = Path::new("/tmp/some-store");
+        let lock = StoreLock::acquire_in(tmp.path(), store)?;
+        let old = std::time::SystemTime::now() - 2 * STALE_AFTER;
+        fs::File::options()
+            .write(true)
+            .open(&lock.path)?
+            .set_modified(old)?;
+
+        assert!(StoreLock::acquire_in(tmp.path(), store).is_err());
+        assert!(lock.path.is_file());
+
+        // a holder that is gone no longer counts, however young its lock
+        fs::write(&lock.path, format!("{}\n", u32::MAX))?;
+        assert!(StoreLock::acquire_in(tmp.path(), store).is_ok());
+        Ok(())
+    }
+}
@@ -1,22 +1,166 @@
-mod app;
-mod backend;
-mod store;
-mod ui;
-
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use pass_tui::clipboard::Clipboard;
+use pass_tui::{app, config, ui};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "pass-tui", version, about = "TUI frontend for pass")]
 struct Cli {
-    /// Path to password store directory
+    /// Path to password store directory; repeat to switch between several stores with `s`
     #[arg(long, global = true)]
-    store: Option<PathBuf>,
+    store: Vec<PathBuf>,
+
+    /// Named profile from the config file to start with
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Directory of the store the list opens in, e.g. `work/aws`; wins over a profile's `cwd`
+    #[arg(long)]
+    cwd: Option<PathBuf>,
+
+    /// Draw inline instead of in the alternate screen, keeping output in scrollback
+    #[arg(long)]
+    no_alt_screen: bool,
+
+    /// Refuse anything that changes the store (edit, add, delete, rename, git pull/push)
+    #[arg(long)]
+    read_only: bool,
+
+    /// Draw without colors; also enabled by a non-empty NO_COLOR environment variable
+    #[arg(long)]
+    no_color: bool,
+
+    /// Draw the tree and icons with ASCII characters, for terminals without Unicode fonts
+    #[arg(long)]
+    ascii: bool,
+
+    /// Neither restore the filter of the last run nor save this one, and forget the saved filter
+    #[arg(long)]
+    no_persist_filter: bool,
+
+    /// Print every pass command (with the store directory) to stderr before running it
+    #[arg(long)]
+    verbose: bool,
+
+    /// Print the commands that would change the store (rm, mv, generate, insert) to stderr
+    /// instead of running them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Print the key of the entry chosen with Enter to stdout and exit, without decrypting it;
+    /// the interface is drawn on stderr, e.g. `pass -c "$(pass-tui --print-selected)"`
+    #[arg(long)]
+    print_selected: bool,
+
+    /// Copy through `pass -c` or pipe the password to a clipboard tool; `auto` picks wl-copy
+    /// under Wayland and xclip or xsel under X11
+    #[arg(long, value_enum)]
+    clipboard: Option<Clipboard>,
+
+    /// Run this command in place of `pass`, e.g. `passage` for age-encrypted stores (whose
+    /// directory is read from `PASSAGE_DIR`)
+    #[arg(long, global = true)]
+    backend_cmd: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check that pass, the store and gpg work, without starting the TUI
+    Doctor,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let mut app = app::App::new_with_store(cli.store)?;
-    ui::run_tui(&mut app)
+    let (mut config, config_error) = match config::Config::load() {
+        Ok(config) => (config, None),
+        Err(err) => (config::Config::default(), Some(format!("{err:#}"))),
+    };
+    if let Some(clipboard) = cli.clipboard {
+        config.clipboard = clipboard;
+    }
+    if let Some(program) = &cli.backend_cmd {
+        // like --store, the flag wins over whatever the profiles name
+        config.backend = Some(program.clone());
+        for profile in config.profiles.values_mut() {
+            profile.backend = None;
+        }
+    }
+    if let Some(Command::Doctor) = cli.command {
+        return doctor(&cli, &config, config_error.as_deref());
+    }
+    if cli.no_alt_screen {
+        config.alt_screen = false;
+    }
+    if cli.no_persist_filter {
+        config.persist_filter = false;
+    }
+    if cli.ascii {
+        config.ascii = Some(true);
+    }
+    config.verbose = cli.verbose;
+    config.dry_run = cli.dry_run;
+    if cli.print_selected {
+        // Nothing is decrypted while picking, not even a delayed preview
+        config.preview_delay_ms = None;
+    }
+    if cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        config.no_color = true;
+    }
+    let mut app =
+        app::App::new_with_profile(cli.store.first().cloned(), cli.profile.as_deref(), config)?;
+    app.read_only = cli.read_only;
+    app.print_selected = cli.print_selected;
+    if let Some(cwd) = &cli.cwd {
+        app.change_dir(cwd);
+    }
+    if cli.store.len() > 1 {
+        app.stores = cli.store;
+    }
+    app.restore_session();
+    if let Some(message) = config_error {
+        // keep the notices found while starting up next to it
+        let status = match app.status.take() {
+            Some(notices) => format!("{message} | {notices}"),
+            None => message,
+        };
+        app.set_status(status);
+    }
+    let result = ui::run_tui(&mut app);
+    if let Err(err) = app.save_session() {
+        eprintln!("pass-tui: could not save the session: {err:#}");
+    }
+    result?;
+    if cli.print_selected {
+        match app.picked {
+            Some(key) => println!("{key}"),
+            None => std::process::exit(1),
+        }
+    }
+    Ok(())
+}
+
+fn doctor(cli: &Cli, config: &config::Config, config_error: Option<&str>) -> Result<()> {
+    let checks = app::App::doctor(cli.store.first().cloned(), cli.profile.as_deref(), config)?;
+    let mut failed = false;
+    if let Some(err) = config_error {
+        failed = true;
+        println!("[FAIL] config: {}", err);
+    }
+    for check in &checks {
+        match &check.outcome {
+            Ok(found) => println!("[ok]   {}: {}", check.name, found),
+            Err(problem) => {
+                failed = true;
+                println!("[FAIL] {}: {}", check.name, problem);
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
 }
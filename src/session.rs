@@ -0,0 +1,97 @@
+use crate::config::state_dir;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// What is kept from one run to the next, in `$XDG_STATE_HOME/pass-tui/session.toml`. The file
+/// is readable by its owner only, since a filter can hint at what the store holds.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// The last applied filter
+    pub filter: String,
+    /// List width in percent last chosen with `<` and `>`
+    pub list_width: Option<u16>,
+    /// Submitted filters, oldest first; only saved with `persist_filter_history`
+    pub filter_history: Vec<String>,
+}
+
+impl Session {
+    pub fn path() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("session.toml"))
+    }
+
+    /// The saved session, or an empty one when nothing was saved yet.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).with_context(|| format!("in {}", path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("reading {}", path.display())),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(toml::to_string(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Forget the saved session, if there is one.
+    pub fn remove(path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path().ok_or_else(no_state_dir)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path().ok_or_else(no_state_dir)?)
+    }
+}
+
+fn no_state_dir() -> anyhow::Error {
+    anyhow!("no state directory for the session file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn session_round_trips_and_is_private() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let path = tmp.path().join("state/session.toml");
+        assert_eq!(Session::load_from(&path)?, Session::default());
+
+        let session = Session {
+            filter: "work/aws".into(),
+            list_width: Some(35),
+            filter_history: vec!["mail".into(), "work/aws".into()],
+        };
+        session.save_to(&path)?;
+        assert_eq!(Session::load_from(&path)?, session);
+        assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+
+        Session::remove(&path)?;
+        Session::remove(&path)?;
+        assert!(!path.exists());
+        Ok(())
+    }
+}
@@ -1,17 +1,24 @@
 use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EntryKind {
     Dir,
     Entry,
+    /// A file that is not encrypted (notes, READMEs), listed when `index_plain` is set
+    Plain,
 }
 
 #[derive(Debug, Clone)]
 pub struct StoreEntry {
     pub path: PathBuf, // path relative to store root, directories end without trailing slash
     pub kind: EntryKind,
+    /// Last modification of the `.gpg` file; `None` for directories or if it could not be read
+    pub modified: Option<SystemTime>,
 }
 
 impl StoreEntry {
@@ -25,7 +32,7 @@ impl StoreEntry {
 
     pub fn relative_entry_path(&self) -> Option<String> {
         match self.kind {
-            EntryKind::Dir => None,
+            EntryKind::Dir | EntryKind::Plain => None,
             EntryKind::Entry => Some(self.store_key()),
         }
     }
@@ -39,7 +46,9 @@ impl StoreEntry {
     }
 }
 
-pub fn build_store_index(root: &Path) -> Result<Vec<StoreEntry>> {
+/// Index the directories and `.gpg` entries below `root`. With `index_plain`, other files are
+/// listed too (keeping their extension), except hidden ones such as `.gpg-id`.
+pub fn build_store_index(root: &Path, index_plain: bool) -> Result<Vec<StoreEntry>> {
     if !root.exists() {
         return Err(anyhow!("Password store not found: {}", root.display()));
     }
@@ -50,11 +59,32 @@ pub fn build_store_index(root: &Path) -> Result<Vec<StoreEntry>> {
     entries.push(StoreEntry {
         path: PathBuf::new(),
         kind: EntryKind::Dir,
+        modified: None,
     });
 
-    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+    // Symlinked subtrees are followed, but each real directory is walked only once so a link
+    // pointing back up the tree cannot loop
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut walker = WalkDir::new(root).follow_links(true).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
         let path = entry.path();
-        if path == root || path.file_name().map_or(false, |name| name == ".git") {
+        if path.file_name().is_some_and(|name| name == ".git") {
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        if entry.file_type().is_dir() {
+            let real = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            if !visited.insert(real) {
+                walker.skip_current_dir();
+                continue;
+            }
+        }
+        if path == root {
             continue;
         }
 
@@ -67,26 +97,44 @@ pub fn build_store_index(root: &Path) -> Result<Vec<StoreEntry>> {
             entries.push(StoreEntry {
                 path: rel.to_path_buf(),
                 kind: EntryKind::Dir,
+                modified: None,
             });
             continue;
         }
 
+        // passage (the age fork of pass) keeps entries in `.age` files
         if entry.file_type().is_file()
-            && path.extension().and_then(|ext| ext.to_str()) == Some("gpg")
+            && matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("gpg" | "age")
+            )
         {
             let mut rel_no_ext = rel.to_path_buf();
             rel_no_ext.set_extension("");
+            // the file may be gone by now; keep it listed without a time rather than fail
+            let modified = entry.metadata().ok().and_then(|meta| meta.modified().ok());
             entries.push(StoreEntry {
                 path: rel_no_ext,
                 kind: EntryKind::Entry,
+                modified,
+            });
+        } else if index_plain
+            && entry.file_type().is_file()
+            && !entry.file_name().to_string_lossy().starts_with('.')
+        {
+            let modified = entry.metadata().ok().and_then(|meta| meta.modified().ok());
+            entries.push(StoreEntry {
+                path: rel.to_path_buf(),
+                kind: EntryKind::Plain,
+                modified,
             });
         }
     }
 
     // Sort: directories first, then entries; lexicographic by relative path
-    entries.sort_by(|a, b| match (a.kind, b.kind) {
-        (EntryKind::Dir, EntryKind::Entry) => std::cmp::Ordering::Less,
-        (EntryKind::Entry, EntryKind::Dir) => std::cmp::Ordering::Greater,
+    entries.sort_by(|a, b| match (a.is_dir(), b.is_dir()) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
         _ => a.path.cmp(&b.path),
     });
 
@@ -105,11 +153,85 @@ pub fn path_to_store_key(path: &Path) -> String {
     key
 }
 
+/// Escape control characters (including the ESC that starts ANSI sequences) so names taken
+/// from the filesystem cannot inject terminal commands. Only for display; keys stay raw.
+pub fn sanitize_for_display(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_control() {
+            out.extend(c.escape_default());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Coarse time since `modified`, e.g. "3d ago", for spotting stale entries.
+pub fn format_age(modified: SystemTime, now: SystemTime) -> String {
+    let secs = now
+        .duration_since(modified)
+        .map(|age| age.as_secs())
+        .unwrap_or(0);
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3_599 => format!("{}m ago", secs / 60),
+        3_600..=86_399 => format!("{}h ago", secs / 3_600),
+        86_400..=2_591_999 => format!("{}d ago", secs / 86_400),
+        2_592_000..=31_535_999 => format!("{}mo ago", secs / 2_592_000),
+        _ => format!("{}y ago", secs / 31_536_000),
+    }
+}
+
+pub fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|mut it| it.next().is_none())
+}
+
+/// Remove `start` (relative to `root`) and each parent above it while they are empty.
+/// Stops at the first non-empty directory and never removes the store root itself.
+/// Returns the removed directories, relative to `root`, deepest first.
+pub fn prune_empty_dirs(root: &Path, start: &Path) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    let mut current = Some(start);
+    while let Some(rel) = current {
+        if rel.as_os_str().is_empty() {
+            break;
+        }
+        let dir = root.join(rel);
+        if !dir.is_dir() || !is_empty_dir(&dir) {
+            break;
+        }
+        fs::remove_dir(&dir)?;
+        removed.push(rel.to_path_buf());
+        current = rel.parent();
+    }
+    Ok(removed)
+}
+
+/// Groups of store keys (entries or directories) that are equal once ASCII case is folded
+/// (`GitHub` and `github`).
+/// These clash on case-insensitive filesystems, where writing one replaces the other.
+/// Each group is sorted, and groups come in order of their folded key.
+pub fn case_collisions(entries: &[StoreEntry]) -> Vec<Vec<String>> {
+    let mut folded: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for entry in entries {
+        let key = entry.store_key();
+        folded
+            .entry(key.to_ascii_lowercase())
+            .or_default()
+            .insert(key);
+    }
+    folded
+        .into_values()
+        .filter(|keys| keys.len() > 1)
+        .map(|keys| keys.into_iter().collect())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_fs::TempDir;
-    use std::fs;
 
     #[test]
     fn index_lists_dirs_and_entries() -> Result<()> {
@@ -119,26 +241,174 @@ mod tests {
         fs::create_dir_all(root.join("x"))?;
         fs::write(root.join("a/b/one.gpg"), b"dummy")?;
         fs::write(root.join("x/two.gpg"), b"dummy")?;
+        fs::write(root.join("x/three.age"), b"dummy")?;
         fs::create_dir_all(root.join(".git"))?;
         fs::write(root.join(".git/ignore"), b"")?;
 
-        let entries = build_store_index(&root)?;
-        // Includes root dir (empty path), plus a, a/b, x, and two entries
+        let entries = build_store_index(&root, false)?;
+        // Includes root dir (empty path), plus a, a/b, x, and three entries (one from passage)
         assert!(entries
             .iter()
             .any(|e| e.kind == EntryKind::Dir && e.path.as_os_str().is_empty()));
         assert!(entries
             .iter()
-            .any(|e| e.kind == EntryKind::Dir && e.path == PathBuf::from("a")));
+            .any(|e| e.kind == EntryKind::Dir && e.path == Path::new("a")));
         assert!(entries
             .iter()
-            .any(|e| e.kind == EntryKind::Dir && e.path == PathBuf::from("a/b")));
+            .any(|e| e.kind == EntryKind::Dir && e.path == Path::new("a/b")));
         assert!(entries
             .iter()
-            .any(|e| e.kind == EntryKind::Entry && e.path == PathBuf::from("a/b/one")));
+            .any(|e| e.kind == EntryKind::Entry && e.path == Path::new("a/b/one")));
         assert!(entries
             .iter()
-            .any(|e| e.kind == EntryKind::Entry && e.path == PathBuf::from("x/two")));
+            .any(|e| e.kind == EntryKind::Entry && e.path == Path::new("x/two")));
+        assert!(entries
+            .iter()
+            .any(|e| e.kind == EntryKind::Entry && e.path == Path::new("x/three")));
+        Ok(())
+    }
+
+    #[test]
+    fn plain_files_are_indexed_only_on_request() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let root = tmp.path().join("store");
+        fs::create_dir_all(root.join("docs"))?;
+        fs::write(root.join("docs/README.md"), b"notes")?;
+        fs::write(root.join("docs/vpn.gpg"), b"dummy")?;
+        fs::write(root.join(".gpg-id"), b"alice@example.com")?;
+
+        let entries = build_store_index(&root, false)?;
+        assert!(!entries.iter().any(|e| e.kind == EntryKind::Plain));
+
+        let entries = build_store_index(&root, true)?;
+        let plain: Vec<&Path> = entries
+            .iter()
+            .filter(|e| e.kind == EntryKind::Plain)
+            .map(|e| e.path.as_path())
+            .collect();
+        assert_eq!(plain, vec![Path::new("docs/README.md")]);
+        Ok(())
+    }
+
+    #[test]
+    fn entries_carry_modification_times() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let root = tmp.path().join("store");
+        fs::create_dir_all(root.join("a"))?;
+        fs::write(root.join("a/one.gpg"), b"dummy")?;
+
+        let entries = build_store_index(&root, false)?;
+        for entry in &entries {
+            assert_eq!(
+                entry.modified.is_some(),
+                !entry.is_dir(),
+                "{:?}",
+                entry.path
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn symlinked_directories_are_followed_without_looping() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new()?;
+        let root = tmp.path().join("store");
+        let shared = tmp.path().join("shared");
+        fs::create_dir_all(&root)?;
+        fs::create_dir_all(&shared)?;
+        fs::write(shared.join("vpn.gpg"), b"dummy")?;
+        symlink(&shared, root.join("team"))?;
+        // a link back to the store root must not be walked again
+        symlink(&root, root.join("team-loop"))?;
+
+        let entries = build_store_index(&root, false)?;
+        assert!(entries
+            .iter()
+            .any(|e| e.kind == EntryKind::Entry && e.path == Path::new("team/vpn")));
+        assert!(!entries.iter().any(|e| e.path.starts_with("team-loop/team")));
+        Ok(())
+    }
+
+    #[test]
+    fn ages_are_rounded_down_to_the_largest_unit() {
+        let now = SystemTime::now();
+        let ago = |secs| format_age(now - std::time::Duration::from_secs(secs), now);
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(90), "1m ago");
+        assert_eq!(ago(3 * 86_400 + 5), "3d ago");
+        assert_eq!(ago(400 * 86_400), "1y ago");
+        // clock skew puts some files in the future
+        assert_eq!(
+            format_age(now + std::time::Duration::from_secs(60), now),
+            "just now"
+        );
+    }
+
+    #[test]
+    fn control_characters_are_escaped_for_display_only() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let root = tmp.path().join("store");
+        fs::create_dir_all(&root)?;
+        fs::write(root.join("evil\x1b[2J\nname.gpg"), b"dummy")?;
+
+        let entries = build_store_index(&root, false)?;
+        let entry = entries
+            .iter()
+            .find(|e| e.kind == EntryKind::Entry)
+            .expect("entry indexed");
+        assert_eq!(entry.store_key(), "evil\x1b[2J\nname");
+        let shown = sanitize_for_display(&entry.display_name());
+        assert_eq!(shown, "evil\\u{1b}[2J\\nname");
+        assert!(!shown.chars().any(char::is_control));
+        Ok(())
+    }
+
+    #[test]
+    fn prune_removes_empty_parents_up_to_first_nonempty() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let root = tmp.path().join("store");
+        fs::create_dir_all(root.join("a/b/c"))?;
+        fs::write(root.join("a/keep.gpg"), b"dummy")?;
+
+        let removed = prune_empty_dirs(&root, Path::new("a/b/c"))?;
+        assert_eq!(removed, vec![PathBuf::from("a/b/c"), PathBuf::from("a/b")]);
+        assert!(!root.join("a/b").exists());
+        assert!(root.join("a/keep.gpg").is_file());
+
+        fs::remove_file(root.join("a/keep.gpg"))?;
+        let removed = prune_empty_dirs(&root, Path::new("a"))?;
+        assert_eq!(removed, vec![PathBuf::from("a")]);
+        assert!(root.is_dir());
+        Ok(())
+    }
+
+    #[test]
+    fn keys_differing_only_by_case_are_grouped() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let root = tmp.path().join("store");
+        fs::create_dir_all(root.join("Web"))?;
+        fs::create_dir_all(root.join("web"))?;
+        for name in [
+            "GitHub.gpg",
+            "github.gpg",
+            "Web/mail.gpg",
+            "web/Mail.gpg",
+            "web/bank.gpg",
+        ] {
+            fs::write(root.join(name), b"dummy")?;
+        }
+
+        let entries = build_store_index(&root, false)?;
+        assert_eq!(
+            case_collisions(&entries),
+            vec![
+                vec!["GitHub".to_string(), "github".to_string()],
+                vec!["Web".to_string(), "web".to_string()],
+                vec!["Web/mail".to_string(), "web/Mail".to_string()],
+            ]
+        );
         Ok(())
     }
 }
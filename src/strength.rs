@@ -0,0 +1,134 @@
+//! A rough, offline estimate of how hard a password is to guess, for the preview header.
+
+use crate::fields::parse_field;
+
+/// Passwords (lowercased, trailing digits and symbols removed) that are guessed first
+const COMMON: [&str; 12] = [
+    "password", "passw0rd", "qwerty", "letmein", "welcome", "admin", "iloveyou", "monkey",
+    "dragon", "abc", "123456", "changeme",
+];
+
+/// Entropy in bits below which each score applies; anything above the last is score 4
+const SCORE_BITS: [f64; 4] = [28.0, 36.0, 60.0, 80.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strength {
+    /// Length in characters
+    pub length: usize,
+    pub lower: bool,
+    pub upper: bool,
+    pub digit: bool,
+    pub symbol: bool,
+    /// 0 (guessed at once) to 4 (out of reach), like zxcvbn
+    pub score: u8,
+}
+
+impl Strength {
+    pub fn label(&self) -> &'static str {
+        ["very weak", "weak", "fair", "strong", "very strong"][usize::from(self.score)]
+    }
+
+    /// The classes present, as `a A 1 #`
+    pub fn classes(&self) -> String {
+        [
+            (self.lower, "a"),
+            (self.upper, "A"),
+            (self.digit, "1"),
+            (self.symbol, "#"),
+        ]
+        .iter()
+        .filter(|(present, _)| *present)
+        .map(|(_, class)| *class)
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+/// Estimate `password`, or `None` when it does not look like a single password: empty, with
+/// spaces, a `key: value` field or a URL.
+pub fn estimate(password: &str) -> Option<Strength> {
+    if password.is_empty()
+        || password.contains(char::is_whitespace)
+        || password.contains("://")
+        || parse_field(password).is_some()
+    {
+        return None;
+    }
+    let chars: Vec<char> = password.chars().collect();
+    let lower = chars.iter().any(char::is_ascii_lowercase);
+    let upper = chars.iter().any(char::is_ascii_uppercase);
+    let digit = chars.iter().any(char::is_ascii_digit);
+    let symbol = chars.iter().any(|c| !c.is_ascii_alphanumeric());
+    let pool = [(lower, 26), (upper, 26), (digit, 10), (symbol, 33)]
+        .iter()
+        .filter(|(present, _)| *present)
+        .map(|(_, size)| size)
+        .sum::<u32>();
+    let per_char = f64::from(pool).log2();
+    // a character repeating or continuing a run (aaa, abc, 321) adds next to nothing
+    let bits: f64 = chars
+        .iter()
+        .enumerate()
+        .map(|(pos, &c)| {
+            let step = pos
+                .checked_sub(1)
+                .map(|prev| (c as i64 - chars[prev] as i64).abs());
+            if matches!(step, Some(0 | 1)) {
+                1.0
+            } else {
+                per_char
+            }
+        })
+        .sum();
+    let stem = password
+        .trim_end_matches(|c: char| !c.is_ascii_alphabetic())
+        .to_lowercase();
+    let score = if COMMON.contains(&stem.as_str()) || COMMON.contains(&password) {
+        0
+    } else {
+        SCORE_BITS
+            .iter()
+            .take_while(|&&limit| bits >= limit)
+            .count() as u8
+    };
+    Some(Strength {
+        length: chars.len(),
+        lower,
+        upper,
+        digit,
+        symbol,
+        score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_grow_with_length_and_variety() {
+        let score = |password| estimate(password).map(|s| s.score);
+        assert_eq!(score("Password1!"), Some(0));
+        assert_eq!(score("123456"), Some(0));
+        assert_eq!(score("aaaaaaaaaaaa"), Some(0));
+        assert_eq!(score("k3x9"), Some(0));
+        assert_eq!(score("tqzmwk"), Some(1));
+        assert_eq!(score("tq7Mw_kf"), Some(2));
+        assert_eq!(score("tq7Mw_kfZ2"), Some(3));
+        assert_eq!(score("R7#vq9!Lmz2@Xw4p"), Some(4));
+
+        let strength = estimate("tq7Mw_kf").unwrap();
+        assert_eq!(strength.length, 8);
+        assert_eq!(strength.classes(), "a A 1 #");
+        assert_eq!(strength.label(), "fair");
+    }
+
+    #[test]
+    fn only_single_passwords_are_rated() {
+        assert!(estimate("").is_none());
+        assert!(estimate("correct horse battery").is_none());
+        assert!(estimate("user: alice").is_none());
+        assert!(estimate("https://example.com").is_none());
+        assert!(estimate("otpauth://totp/x").is_none());
+    }
+}
@@ -0,0 +1,255 @@
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Deserializer};
+use std::env;
+
+/// Built-in color schemes; a `[theme]` table starts from one and overrides single colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Dark,
+    Light,
+    /// Terminal default colors only; selection is shown in reverse video
+    Nocolor,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "ThemeConfig")]
+pub struct Theme {
+    /// Tree connector glyphs (`│`, `├─`, `└─`)
+    pub tree: Color,
+    /// Selected row of the list and of pickers
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// Filter matches, status messages and other text that should stand out
+    pub highlight: Color,
+    /// Failed previews and the borders of confirmation dialogs
+    pub error: Color,
+    /// Hints, ages and placeholders
+    pub dim: Color,
+    /// Borders of input and picker dialogs
+    pub border: Color,
+}
+
+impl Theme {
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Dark => Self {
+                tree: Color::DarkGray,
+                selection_fg: Color::Yellow,
+                selection_bg: Color::Reset,
+                highlight: Color::Yellow,
+                error: Color::Red,
+                dim: Color::DarkGray,
+                border: Color::Cyan,
+            },
+            Preset::Light => Self {
+                tree: Color::Gray,
+                selection_fg: Color::Blue,
+                selection_bg: Color::Reset,
+                highlight: Color::Magenta,
+                error: Color::Red,
+                dim: Color::DarkGray,
+                border: Color::Blue,
+            },
+            Preset::Nocolor => Self {
+                tree: Color::Reset,
+                selection_fg: Color::Reset,
+                selection_bg: Color::Reset,
+                highlight: Color::Reset,
+                error: Color::Reset,
+                dim: Color::Reset,
+                border: Color::Reset,
+            },
+        }
+    }
+
+    /// Style of the selected row; without any selection colors it falls back to reverse video.
+    pub fn selection(&self) -> Style {
+        let style = Style::default()
+            .fg(self.selection_fg)
+            .bg(self.selection_bg)
+            .add_modifier(Modifier::BOLD);
+        if self.selection_fg == Color::Reset && self.selection_bg == Color::Reset {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
+    }
+
+    /// Style of text that should stand out; bold when the highlight color is the default.
+    pub fn highlight(&self) -> Style {
+        if self.highlight == Color::Reset {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(self.highlight)
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(Preset::Dark)
+    }
+}
+
+/// Characters drawn for the tree, the entry icons and row markers.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Tree column of an ancestor with more rows below it
+    pub branch: &'static str,
+    /// Tree column of an ancestor that was the last of its siblings
+    pub blank: &'static str,
+    pub tee: &'static str,
+    pub last: &'static str,
+    pub dir: &'static str,
+    pub entry: &'static str,
+    pub plain: &'static str,
+    pub marked: &'static str,
+    pub cursor: &'static str,
+    /// Header marker for a store with uncommitted git changes
+    pub dirty: &'static str,
+    /// Filled and empty cells of the password strength meter
+    pub meter_full: &'static str,
+    pub meter_empty: &'static str,
+}
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        branch: "│  ",
+        blank: "   ",
+        tee: "├─ ",
+        last: "└─ ",
+        dir: "📁 ",
+        entry: "📄 ",
+        plain: "📝 ",
+        marked: "✓ ",
+        cursor: "▶ ",
+        dirty: "●",
+        meter_full: "█",
+        meter_empty: "░",
+    };
+
+    /// For terminals or fonts without box-drawing characters and emoji
+    pub const ASCII: Glyphs = Glyphs {
+        branch: "|  ",
+        blank: "   ",
+        tee: "+- ",
+        last: "`- ",
+        dir: "[D] ",
+        entry: "[F] ",
+        plain: "[T] ",
+        marked: "* ",
+        cursor: "> ",
+        dirty: "*",
+        meter_full: "#",
+        meter_empty: "-",
+    };
+
+    /// `ascii` when set, otherwise ASCII unless the environment suggests Unicode works.
+    pub fn select(ascii: Option<bool>) -> &'static Glyphs {
+        let ascii = ascii.unwrap_or_else(|| {
+            let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+                .into_iter()
+                .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()));
+            !unicode_expected(locale.as_deref(), env::var("TERM").ok().as_deref())
+        });
+        if ascii {
+            &Glyphs::ASCII
+        } else {
+            &Glyphs::UNICODE
+        }
+    }
+}
+
+/// Whether a terminal with this locale and `TERM` can be expected to draw Unicode: the Linux
+/// console and dumb terminals cannot, nor can a locale that is set but not UTF-8. With no locale
+/// at all there is nothing to go on, so Unicode is assumed as before.
+fn unicode_expected(locale: Option<&str>, term: Option<&str>) -> bool {
+    if matches!(term, Some("linux" | "dumb")) {
+        return false;
+    }
+    locale.is_none_or(|locale| {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    })
+}
+
+/// The `[theme]` table as written: a preset plus the colors that override it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeConfig {
+    preset: Preset,
+    #[serde(deserialize_with = "deserialize_color")]
+    tree: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    selection_fg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    selection_bg: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    highlight: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    error: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    dim: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    border: Option<Color>,
+}
+
+impl From<ThemeConfig> for Theme {
+    fn from(config: ThemeConfig) -> Self {
+        let base = Theme::preset(config.preset);
+        Self {
+            tree: config.tree.unwrap_or(base.tree),
+            selection_fg: config.selection_fg.unwrap_or(base.selection_fg),
+            selection_bg: config.selection_bg.unwrap_or(base.selection_bg),
+            highlight: config.highlight.unwrap_or(base.highlight),
+            error: config.error.unwrap_or(base.error),
+            dim: config.dim.unwrap_or(base.dim),
+            border: config.border.unwrap_or(base.border),
+        }
+    }
+}
+
+/// Accept ratatui color names (`darkgray`, `light-blue`), indices (`8`) and hex (`#aabbcc`).
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let name = String::deserialize(deserializer)?;
+    name.parse()
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("unknown color '{}'", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_override_the_chosen_preset() {
+        let theme: Theme = toml::from_str("preset = \"light\"\nerror = \"#ff0000\"").unwrap();
+        assert_eq!(theme.error, Color::Rgb(0xff, 0, 0));
+        assert_eq!(theme.border, Theme::preset(Preset::Light).border);
+
+        let nocolor = Theme::preset(Preset::Nocolor);
+        assert!(nocolor
+            .selection()
+            .add_modifier
+            .contains(Modifier::REVERSED));
+        assert!(toml::from_str::<Theme>("preset = \"sepia\"").is_err());
+    }
+
+    #[test]
+    fn ascii_is_expected_on_the_console_and_without_utf8() {
+        assert!(unicode_expected(
+            Some("en_US.UTF-8"),
+            Some("xterm-256color")
+        ));
+        assert!(unicode_expected(Some("C.utf8"), None));
+        assert!(unicode_expected(None, Some("xterm")));
+        assert!(!unicode_expected(Some("en_US.UTF-8"), Some("linux")));
+        assert!(!unicode_expected(Some("C"), Some("xterm")));
+        assert_eq!(Glyphs::select(Some(true)), &Glyphs::ASCII);
+    }
+}
@@ -1,39 +1,127 @@
-use crate::app::{App, Modal, PendingAction, PreviewMode};
-use crate::store::{path_to_store_key, StoreEntry};
+use crate::app::{fuzzy_match, App, Modal, PendingAction, PreviewMode, ViewRow};
+use crate::config::EnterAction;
+use crate::fields::{parse_entry, parse_field};
+use crate::keymap::Action;
+use crate::lock::StoreLock;
+use crate::store::{format_age, path_to_store_key, sanitize_for_display, EntryKind};
+use crate::strength::Strength;
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    MouseButton, MouseEvent, MouseEventKind,
+};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use qrcode::QrCode;
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap};
-use ratatui::Terminal;
-use std::io;
-use std::time::Duration;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+const WORKING_STATUS: &str = "Working…";
+const READ_ONLY_STATUS: &str = "Read-only mode: the store cannot be changed";
+/// Lines moved by PageUp/PageDown in the preview pane
+const PREVIEW_PAGE: i32 = 10;
+/// Columns the unwrapped preview pans per key press
+const PREVIEW_PAN: i32 = 8;
+/// Percentage points `<` and `>` move the divider between list and preview
+const LIST_WIDTH_STEP: i16 = 5;
+/// Lines moved per mouse wheel notch in the preview pane
+const WHEEL_LINES: i32 = 3;
+/// Event poll interval while a preview is pending or decrypting in the background
+const PREVIEW_POLL: Duration = Duration::from_millis(50);
+/// Event poll interval while a countdown (clipboard, flash, peek, jump) is on screen
+const TICK_POLL: Duration = Duration::from_millis(250);
+/// Event poll interval while the store watcher may report changes
+const WATCH_POLL: Duration = Duration::from_secs(1);
+/// Event poll interval when nothing but a key press can change the screen
+const IDLE_POLL: Duration = Duration::from_secs(5);
+const OK_BUTTON: &str = "[ OK ]";
+const CANCEL_BUTTON: &str = "[ Cancel ]";
+const BUTTON_GAP: u16 = 2;
+/// Smallest centered dialog: room for both buttons, and for a line of message between borders
+const MODAL_MIN_SIZE: (u16, u16) = (
+    OK_BUTTON.len() as u16 + BUTTON_GAP + CANCEL_BUTTON.len() as u16 + 6,
+    5,
+);
+
+type Tui = Terminal<CrosstermBackend<Box<dyn Write>>>;
 
 pub fn run_tui(app: &mut App) -> Result<()> {
+    let alt_screen = app.config.alt_screen;
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    // With --print-selected stdout carries the result, so draw on stderr instead
+    let mut stdout: Box<dyn Write> = if app.print_selected {
+        Box::new(io::BufWriter::new(io::stderr()))
+    } else {
+        Box::new(io::stdout())
+    };
+    crossterm::execute!(stdout, EnableMouseCapture)?;
+    let mut terminal = if alt_screen {
+        crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+        Terminal::new(CrosstermBackend::new(stdout))?
+    } else {
+        // Draw inline so the last frame (and earlier output) stays in scrollback
+        let (_, height) = crossterm::terminal::size()?;
+        Terminal::with_options(
+            CrosstermBackend::new(stdout),
+            TerminalOptions {
+                viewport: Viewport::Inline(height),
+            },
+        )?
+    };
 
     let res = run(app, &mut terminal);
 
     disable_raw_mode()?;
-    crossterm::execute!(
-        terminal.backend_mut(),
-        crossterm::terminal::LeaveAlternateScreen,
-        crossterm::cursor::Show
-    )?;
+    crossterm::execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    if alt_screen {
+        crossterm::execute!(
+            terminal.backend_mut(),
+            crossterm::terminal::LeaveAlternateScreen,
+            crossterm::cursor::Show
+        )?;
+    } else {
+        // Park the cursor below the last frame so the shell prompt does not overwrite it
+        let bottom = terminal.get_frame().size().bottom();
+        crossterm::execute!(
+            terminal.backend_mut(),
+            crossterm::cursor::MoveTo(0, bottom.saturating_sub(1)),
+            crossterm::cursor::Show
+        )?;
+        writeln!(terminal.backend_mut())?;
+    }
     terminal.show_cursor()?;
 
     res
 }
 
-fn run(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
-    let poll_timeout = Duration::from_millis(500);
+/// How long to wait for input before checking on background work: as short as whatever is
+/// running needs, and long when idle so the TUI costs next to no CPU.
+fn poll_timeout(app: &App) -> Duration {
+    if app.preview_loading.is_some() || app.preview_pending_since.is_some() {
+        // so the preview shows up promptly
+        PREVIEW_POLL
+    } else if app.clipboard_expires_at.is_some()
+        || app.flash_deadline.is_some()
+        || app.peek_deadline.is_some()
+        || app.jump_deadline.is_some()
+    {
+        TICK_POLL
+    } else if app.watching_store() {
+        WATCH_POLL
+    } else {
+        IDLE_POLL
+    }
+}
+
+fn run(app: &mut App, terminal: &mut Tui) -> Result<()> {
+    let alt_screen = app.config.alt_screen;
     app.apply_filter();
     app.update_preview();
     let mut needs_redraw = true;
@@ -43,12 +131,14 @@ fn run(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) ->
             needs_redraw = false;
         }
 
-        if crossterm::event::poll(poll_timeout)? {
+        if crossterm::event::poll(poll_timeout(app))? {
             match event::read()? {
                 Event::Key(key) => {
-                    if handle_key(app, key)? {
-                        needs_redraw = true;
-                    }
+                    needs_redraw |= handle_key(app, key)?;
+                }
+                Event::Mouse(mouse) => {
+                    let area = terminal.get_frame().size();
+                    needs_redraw |= handle_mouse(app, mouse, area);
                 }
                 Event::Resize(width, height) => {
                     terminal.resize(Rect::new(0, 0, width, height))?;
@@ -58,36 +148,93 @@ fn run(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) ->
             }
         }
 
+        needs_redraw |= app.poll_store_changes();
+        needs_redraw |= app.debounce_preview();
+        needs_redraw |= app.poll_preview();
+
         // Run any pending actions. Suspend only for interactive ones (edit/add).
+        if app.flash_deadline.is_some() {
+            // tick the countdown, then hide the secret once it expires
+            app.expire_flash();
+            needs_redraw = true;
+        }
+        if app.clipboard_expires_at.is_some() {
+            app.expire_clipboard();
+            needs_redraw = true;
+        }
+        if app.peek_deadline.is_some() {
+            needs_redraw |= app.expire_peek();
+        }
+        if app.jump_deadline.is_some() {
+            app.expire_jump();
+            needs_redraw |= app.jump_deadline.is_none();
+        }
+
+        if app.read_only && app.pending.as_ref().is_some_and(PendingAction::is_write) {
+            // the key handlers refuse these already; this also covers modals and mouse clicks
+            app.pending = None;
+            app.set_status(READ_ONLY_STATUS.to_string());
+            needs_redraw = true;
+        }
         if let Some(action) = app.pending.take() {
+            // Advisory only: if another instance holds the lock, warn but carry on
+            let (_lock, lock_warning) = if action.is_mutating() {
+                match StoreLock::acquire(&app.store_dir) {
+                    Ok(lock) => (Some(lock), None),
+                    Err(err) => (None, Some(format!("Warning: {}", err))),
+                }
+            } else {
+                (None, None)
+            };
             let res = match action {
+                // a missing editor would only fail once the screen is gone, so check first
                 PendingAction::Edit(_) | PendingAction::Add(_) => {
-                    suspend_and_run(terminal, || run_action(app, action))
+                    match app.backend.check_editor() {
+                        Err(err) => Err(err),
+                        Ok(()) => suspend_and_run(terminal, alt_screen, || run_action(app, action)),
+                    }
+                }
+                // generate -i decrypts the entry first, which may prompt for a passphrase, and
+                // git over ssh may ask for a key passphrase
+                PendingAction::Pager(_)
+                | PendingAction::Regenerate(_)
+                | PendingAction::GitPull
+                | PendingAction::GitPush
+                | PendingAction::Grep(_) => {
+                    suspend_and_run(terminal, alt_screen, || run_action(app, action))
+                }
+                _ => {
+                    // Show feedback while the action runs; the screen stays up for these
+                    app.status = Some(WORKING_STATUS.to_string());
+                    terminal.draw(|f| draw_ui(f, app))?;
+                    run_action(app, action)
                 }
-                _ => run_action(app, action),
             };
+            if app.status.as_deref() == Some(WORKING_STATUS) {
+                app.status = None;
+            }
             if let Err(e) = res {
-                app.status = Some(e.to_string());
+                app.set_status(e.to_string());
+            } else if let Some(warning) = lock_warning {
+                app.set_status(warning);
             }
             if let Err(e) = app.refresh() {
-                app.status = Some(e.to_string());
+                app.set_status(e.to_string());
             }
             app.update_preview();
             needs_redraw = true;
         }
 
         if let Some((rel, mode)) = app.take_pending_preview() {
-            let qr = mode == PreviewMode::Qr;
             let backend = app.backend.as_ref();
             let entry_for_unlock = rel.clone();
-            let unlock_result =
-                suspend_and_run(terminal, move || backend.unlock(&entry_for_unlock, qr));
+            let unlock_result = suspend_and_run(terminal, alt_screen, move || {
+                backend.unlock(&entry_for_unlock)
+            });
             if let Err(e) = unlock_result {
-                app.status = Some(e.to_string());
-            }
-            if let Err(e) = app.load_preview_after_unlock(rel, mode) {
-                app.status = Some(e.to_string());
+                app.set_status(e.to_string());
             }
+            app.load_preview_after_unlock(rel, mode);
             needs_redraw = true;
         }
 
@@ -98,20 +245,120 @@ fn run(app: &mut App, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) ->
     Ok(())
 }
 
-fn draw_ui(f: &mut ratatui::Frame<'_>, app: &App) {
+/// Program title and (with several stores) store name shown before the breadcrumb.
+fn header_prefix(app: &App) -> (String, String) {
+    let mut title = match &app.profile {
+        Some(profile) => format!("pass-tui [{}]  ", sanitize_for_display(profile)),
+        None => "pass-tui  ".to_string(),
+    };
+    if app.read_only {
+        title.insert_str(title.len() - 2, " [read-only]");
+    }
+    let store = if app.stores.len() > 1 {
+        format!("{}:/", sanitize_for_display(&app.store_name()))
+    } else {
+        String::new()
+    };
+    (title, store)
+}
+
+/// Header columns of each breadcrumb segment and the directory it leads to; the title and store
+/// name before the breadcrumb lead to the store root.
+fn breadcrumb_targets(app: &App) -> Vec<(Range<u16>, PathBuf)> {
+    let (title, store) = header_prefix(app);
+    let mut x = (Line::from(title).width() + Line::from(store).width()) as u16;
+    let mut targets = vec![(0..x, PathBuf::new())];
+    let mut dir = PathBuf::new();
+    for component in app.cwd.iter() {
+        dir.push(component);
+        let width = Line::from(sanitize_for_display(&component.to_string_lossy())).width() as u16;
+        targets.push((x..x + width, dir.clone()));
+        // skip the separating '/'
+        x += width + 1;
+    }
+    targets
+}
+
+/// Header line, list pane and preview pane for a frame of the given size.
+fn screen_layout(area: Rect, app: &App) -> (Rect, Rect, Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1), Constraint::Min(2)])
-        .split(f.size());
+        .split(area);
+    let body_constraints = match (app.list_width, app.config.preview_width) {
+        (Some(percent), _) => [
+            Constraint::Percentage(percent),
+            Constraint::Percentage(100 - percent),
+        ],
+        (None, Some(width)) => [Constraint::Min(0), Constraint::Length(width)],
+        (None, None) => [Constraint::Percentage(50), Constraint::Percentage(50)],
+    };
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(body_constraints)
+        .split(chunks[1]);
+    (chunks[0], body[0], body[1])
+}
+
+/// Message area and button row inside a confirm dialog. The buttons keep the last row however
+/// little room there is; the message gets what remains, possibly nothing.
+fn confirm_layout(area: Rect) -> (Rect, Rect) {
+    let inner = area.inner(&ratatui::layout::Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let buttons_height = inner.height.min(1);
+    let message = Rect {
+        height: inner.height - buttons_height,
+        ..inner
+    };
+    let buttons = Rect {
+        y: inner.y + message.height,
+        height: buttons_height,
+        ..inner
+    };
+    (message, buttons)
+}
+
+/// Where dialogs are drawn: centered on the screen, or over all of it when the centered box would
+/// be too small to show a message and the buttons.
+fn modal_area(screen: Rect) -> Rect {
+    let area = centered_rect(60, 40, screen);
+    if area.width < MODAL_MIN_SIZE.0 || area.height < MODAL_MIN_SIZE.1 {
+        screen
+    } else {
+        area
+    }
+}
+
+fn draw_ui(f: &mut ratatui::Frame<'_>, app: &mut App) {
+    let (header_area, list_area, preview_area) = screen_layout(f.size(), app);
+    app.list_height = list_area.height.saturating_sub(2);
+    let app = &*app;
+    let theme = &app.theme;
 
     // Breadcrumb and header right content (help or filter)
-    let breadcrumb = app
-        .cwd
-        .iter()
-        .filter_map(|c| c.to_str())
-        .collect::<Vec<_>>()
-        .join("/");
-    let header_right = if app.filter_mode || !app.filter.is_empty() {
+    let breadcrumb = sanitize_for_display(
+        &app.cwd
+            .iter()
+            .filter_map(|c| c.to_str())
+            .collect::<Vec<_>>()
+            .join("/"),
+    );
+    let header_right = if let Some(secret) = &app.peek {
+        Line::from(vec![
+            Span::styled("Password: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(sanitize_for_display(secret), theme.highlight()),
+        ])
+    } else if let Some(input) = &app.goto_input {
+        Line::from(vec![
+            Span::raw(" ["),
+            Span::styled("Go to row:", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" "),
+            Span::styled(input.as_str(), theme.highlight()),
+            Span::raw("]"),
+        ])
+    } else if app.filter_mode || !app.filter.is_empty() {
         Line::from(vec![
             Span::raw(" ["),
             Span::styled("Filter:", Style::default().add_modifier(Modifier::BOLD)),
@@ -122,106 +369,227 @@ fn draw_ui(f: &mut ratatui::Frame<'_>, app: &App) {
                 } else {
                     app.filter.as_str()
                 },
-                Style::default().fg(Color::Yellow),
+                theme.highlight(),
             ),
             Span::raw("]"),
         ])
-    } else if let Some(msg) = &app.status {
+    } else if let Some(prefix) = &app.jump_prefix {
+        Line::from(vec![
+            Span::raw(" ["),
+            Span::styled("Jump:", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" "),
+            Span::styled(prefix.as_str(), theme.highlight()),
+            Span::raw("]"),
+        ])
+    } else if let Some(msg) = app.status.clone().or_else(|| app.clipboard_status()) {
         Line::from(vec![Span::styled(
-            msg.as_str(),
-            Style::default().fg(Color::Yellow),
+            sanitize_for_display(&msg),
+            theme.highlight(),
         )])
     } else {
-        Line::from("[/] filter  [a] add  [c] qr code  [d] delete  [e] edit  [enter] view  [h/l/←/→] collapse/expand  [j/k/↑/↓] move  [q] quit  [r] rename  [y] yank")
+        Line::from(format!("[/] filter  [a] add  [c] qr code  [d] delete  [e] edit  [enter] {}  [h/l/←/→] collapse/expand  [j/k/↑/↓] move  [q] quit  [r] rename  [y] yank", app.config.enter_action.help()))
     };
-    f.render_widget(Clear, chunks[0]);
-    let header = Paragraph::new(Line::from(vec![
-        Span::raw("pass-tui  "),
+    f.render_widget(Clear, header_area);
+    let (title, store) = header_prefix(app);
+    let mut header_left = Line::from(vec![
+        Span::raw(title),
+        Span::styled(store, Style::default().add_modifier(Modifier::BOLD)),
         Span::raw(breadcrumb),
         Span::raw("  "),
-    ]))
-    .wrap(Wrap { trim: true });
-    f.render_widget(header, chunks[0]);
-    // Render the right-side content by drawing another Paragraph overlaid aligned to right
+    ]);
+    if app.git_dirty {
+        header_left.spans.push(Span::styled(
+            format!("{} uncommitted  ", app.glyphs.dirty),
+            theme.highlight(),
+        ));
+    }
+    // The right-side content gets whatever the breadcrumb leaves, so segments stay clickable
+    let left_width = (header_left.width() as u16).min(header_area.width);
+    let header = Paragraph::new(header_left).wrap(Wrap { trim: true });
+    f.render_widget(header, header_area);
+    let right_area = Rect {
+        x: header_area.x + left_width,
+        width: header_area.width - left_width,
+        ..header_area
+    };
     let right = Paragraph::new(header_right).wrap(Wrap { trim: true });
-    f.render_widget(right, chunks[0]);
+    f.render_widget(right, right_area);
 
     // Body: list + raw preview
-    f.render_widget(Clear, chunks[1]);
-    let body = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[1]);
+    f.render_widget(Clear, list_area.union(preview_area));
 
+    // borders and the highlight symbol take four columns
+    let row_width = usize::from(list_area.width.saturating_sub(4));
+    let now = SystemTime::now();
+    let number_width = app.rows.len().to_string().len();
     let items: Vec<ListItem> = app
         .rows
         .iter()
-        .map(|row| {
-            render_row(
-                &app.entries[row.idx],
-                &row.branches,
-                app.filter_mode,
-                if app.filter_mode {
-                    app.filter_input.as_str()
-                } else {
-                    app.filter.as_str()
-                },
-            )
+        .enumerate()
+        .map(|(pos, row)| {
+            let entry = &app.entries[row.idx];
+            let name_width = if app.config.line_numbers {
+                row_width.saturating_sub(number_width + 1)
+            } else {
+                row_width
+            };
+            let mut line = render_row(app, row, pos == app.cursor, name_width);
+            if app.config.line_numbers {
+                let number = format!("{:>width$} ", pos + 1, width = number_width);
+                line.spans
+                    .insert(0, Span::styled(number, Style::default().fg(theme.dim)));
+            }
+            if let Some(modified) = entry.modified {
+                push_right_aligned(&mut line, format_age(modified, now), row_width, theme.dim);
+            }
+            ListItem::new(line)
         })
         .collect();
-    let store_title = app.store_dir.to_string_lossy().into_owned();
+    let store_title = sanitize_for_display(&app.store_dir.to_string_lossy());
     let list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title(store_title))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("▶ ");
+        .highlight_style(theme.selection())
+        .highlight_symbol(app.glyphs.cursor);
     let mut state = list_state(app);
-    f.render_stateful_widget(list, body[0], &mut state);
+    f.render_stateful_widget(list, list_area, &mut state);
+    if app.rows.is_empty() && !app.entries.iter().any(|e| !e.is_dir()) {
+        let hint = Paragraph::new("No entries yet — press a to add one")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.dim))
+            .wrap(Wrap { trim: true });
+        let inner = list_area.inner(&Margin::new(1, 1));
+        let middle = Rect {
+            y: inner.y + inner.height.saturating_sub(1) / 2,
+            height: inner.height.min(2),
+            ..inner
+        };
+        f.render_widget(hint, middle);
+    }
 
     let mut style = Style::default();
-    let current_sel = app.selected_entry_path();
+    let current_sel = app.selected_preview_key();
     let mut raw_text: String = String::new();
+    let masked = app.preview_masked
+        && matches!(app.preview_mode, PreviewMode::Raw | PreviewMode::Fields)
+        && !app.preview_is_error;
     if let (Some(sel), Some(prev)) = (current_sel.as_ref(), app.preview_key.as_ref()) {
-        if sel == prev {
-            raw_text = app.preview_text.clone();
+        if sel == prev && app.preview_mode == PreviewMode::Fields && !app.preview_is_error {
+            raw_text = field_table(&app.preview_text, masked);
+        } else if sel == prev {
+            raw_text = app
+                .preview_text
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    if masked {
+                        mask_line(i, line)
+                    } else {
+                        sanitize_for_display(line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
         }
     }
-    if raw_text.is_empty() {
+    let preview_title = if masked && !raw_text.is_empty() {
+        "Preview (v to reveal)"
+    } else {
+        "Preview"
+    };
+    let loading = app.preview_loading.is_some() && current_sel == app.preview_key;
+    if loading {
+        raw_text = "Decrypting…".to_string();
+        style = style.fg(theme.dim);
+    } else if raw_text.is_empty() {
         raw_text = "Press Enter (or C for QR code) to view selected file".to_string();
-        style = style.fg(Color::DarkGray);
+        style = style.fg(theme.dim);
     } else if app.preview_is_error {
-        style = style.fg(Color::Red);
+        style = style.fg(theme.error);
     }
-    let raw = Paragraph::new(raw_text)
-        .wrap(Wrap { trim: false })
-        .block(Block::default().borders(Borders::ALL).title("Preview"))
-        .style(style);
-    f.render_widget(raw, body[1]);
+    let showing_qr = app.preview_mode == PreviewMode::Qr
+        && !loading
+        && !app.preview_is_error
+        && current_sel.is_some()
+        && current_sel == app.preview_key;
+    let raw = if showing_qr {
+        let inner_width = preview_area.width.saturating_sub(2);
+        Paragraph::new(render_qr(&app.preview_text, inner_width)).scroll((app.preview_scroll, 0))
+    } else if app.preview_wrap {
+        Paragraph::new(raw_text)
+            .wrap(Wrap { trim: false })
+            .style(style)
+            .scroll((app.preview_scroll, 0))
+    } else {
+        Paragraph::new(raw_text)
+            .style(style)
+            .scroll((app.preview_scroll, app.preview_hscroll))
+    };
+    let block = Block::default().borders(Borders::ALL).title(preview_title);
+    let mut content_area = block.inner(preview_area);
+    f.render_widget(block, preview_area);
+    let strength = app.preview_strength.filter(|_| {
+        !loading && !app.preview_is_error && !showing_qr && current_sel == app.preview_key
+    });
+    if let (Some(strength), true) = (strength, content_area.height > 1) {
+        let header = Rect {
+            height: 1,
+            ..content_area
+        };
+        f.render_widget(Paragraph::new(strength_line(app, strength)), header);
+        content_area.y += 1;
+        content_area.height -= 1;
+    }
+    f.render_widget(raw, content_area);
 
     // Footer removed to avoid persistent bottom line
 
     // Modal overlay
     if let Some(m) = &app.modal {
-        let area = centered_rect(60, 40, f.size());
+        let area = modal_area(f.size());
         f.render_widget(Clear, area); // clear the area beneath
         match m {
-            Modal::Input { title, buffer, .. } => {
+            Modal::Input {
+                title,
+                buffer,
+                error,
+                ..
+            } => {
                 let block = Block::default()
                     .title(title.as_str())
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Cyan));
-                let text = Paragraph::new(vec![
-                    Line::from(buffer.as_str()),
-                    Line::from(Span::styled(
-                        "Enter to create, Esc to cancel",
-                        Style::default().fg(Color::DarkGray),
-                    )),
-                ])
-                .wrap(Wrap { trim: false })
-                .block(block);
+                    .border_style(Style::default().fg(theme.border));
+                let mut lines = vec![Line::from(sanitize_for_display(buffer))];
+                if let Some(error) = error {
+                    lines.push(Line::from(Span::styled(
+                        error.as_str(),
+                        Style::default().fg(theme.error),
+                    )));
+                }
+                lines.push(Line::from(Span::styled(
+                    "Enter to create, Esc to cancel",
+                    Style::default().fg(theme.dim),
+                )));
+                let text = Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(block);
+                f.render_widget(text, area);
+            }
+            Modal::Multiline { title, buffer, .. } => {
+                let block = Block::default()
+                    .title(title.as_str())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border));
+                let mut lines: Vec<Line> = buffer
+                    .split('\n')
+                    .map(|line| Line::from(sanitize_for_display(line)))
+                    .collect();
+                lines.push(Line::from(Span::styled(
+                    "First line is the password. Ctrl-s to save, Esc to cancel",
+                    Style::default().fg(theme.dim),
+                )));
+                let text = Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(block);
                 f.render_widget(text, area);
             }
             Modal::Confirm {
@@ -233,103 +601,334 @@ fn draw_ui(f: &mut ratatui::Frame<'_>, app: &App) {
                 let block = Block::default()
                     .title(title.as_str())
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Red));
+                    .border_style(Style::default().fg(theme.error));
                 // Render message and buttons
-                let msg = Paragraph::new(message.as_str()).wrap(Wrap { trim: true });
+                let lines: Vec<Line> = message
+                    .lines()
+                    .map(|line| Line::from(sanitize_for_display(line)))
+                    .collect();
+                let msg = Paragraph::new(lines).wrap(Wrap { trim: true });
                 f.render_widget(block, area);
-                let inner = area.inner(&ratatui::layout::Margin {
-                    vertical: 1,
-                    horizontal: 2,
-                });
-                let rows = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Min(1), Constraint::Length(1)])
-                    .split(inner);
-                f.render_widget(msg, rows[0]);
+                let (message_area, button_area) = confirm_layout(area);
+                f.render_widget(msg, message_area);
                 // Buttons
                 let ok_style = if *selected_ok {
-                    Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
+                    theme
+                        .highlight()
+                        .add_modifier(Modifier::REVERSED | Modifier::BOLD)
                 } else {
-                    Style::default().fg(Color::Yellow)
+                    theme.highlight()
                 };
                 let cancel_style = if !*selected_ok {
-                    Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
+                    theme
+                        .highlight()
+                        .add_modifier(Modifier::REVERSED | Modifier::BOLD)
                 } else {
-                    Style::default().fg(Color::Yellow)
+                    theme.highlight()
                 };
                 let buttons = Paragraph::new(Line::from(vec![
-                    Span::styled("[ OK ]  ", ok_style),
-                    Span::styled("[ Cancel ]", cancel_style),
+                    Span::styled(OK_BUTTON, ok_style),
+                    Span::raw(" ".repeat(BUTTON_GAP as usize)),
+                    Span::styled(CANCEL_BUTTON, cancel_style),
                 ]));
-                f.render_widget(buttons, rows[1]);
+                f.render_widget(buttons, button_area);
+            }
+            Modal::Info { title, lines } => {
+                let block = Block::default()
+                    .title(title.as_str())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border));
+                let mut text: Vec<Line> = lines
+                    .iter()
+                    .map(|line| Line::from(sanitize_for_display(line)))
+                    .collect();
+                text.push(Line::from(Span::styled(
+                    "Esc to close",
+                    Style::default().fg(theme.dim),
+                )));
+                let info = Paragraph::new(text).wrap(Wrap { trim: false }).block(block);
+                f.render_widget(info, area);
+            }
+            Modal::Picker {
+                title,
+                items,
+                selected,
+                ..
+            } => {
+                let block = Block::default()
+                    .title(title.as_str())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border));
+                let list = List::new(
+                    items
+                        .iter()
+                        .map(|item| ListItem::new(sanitize_for_display(item)))
+                        .collect::<Vec<_>>(),
+                )
+                .block(block)
+                .highlight_style(theme.selection())
+                .highlight_symbol(app.glyphs.cursor);
+                let mut state = ratatui::widgets::ListState::default();
+                state.select(Some(*selected));
+                f.render_stateful_widget(list, area, &mut state);
+            }
+            Modal::Flash { entry, secret } => {
+                let remaining = app
+                    .flash_deadline
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+                    .unwrap_or_default();
+                let block = Block::default()
+                    .title(sanitize_for_display(entry))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border));
+                let text = Paragraph::new(vec![
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        sanitize_for_display(secret),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        format!("Hides in {}s, any key to hide now", remaining.as_secs() + 1),
+                        Style::default().fg(theme.dim),
+                    )),
+                ])
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: false })
+                .block(block);
+                f.render_widget(text, area);
             }
         }
     }
 }
 
-fn render_row(
-    e: &StoreEntry,
-    branches: &[bool],
-    filter_active: bool,
-    filter: &str,
-) -> ListItem<'static> {
+/// One row of the list, at most `width` columns: a name too long for it is cut short. Matches of
+/// the filter being typed are highlighted. Rows without branches are from the flat view and show
+/// the whole path, which is cut in the middle to keep both ends.
+fn render_row(app: &App, row: &ViewRow, selected: bool, width: usize) -> Line<'static> {
+    let e = &app.entries[row.idx];
+    let branches = &row.branches;
+    let theme = &app.theme;
+    let glyphs = app.glyphs;
     let mut prefix = String::new();
     if let Some((&is_last, parents)) = branches.split_last() {
         for branch in parents {
-            prefix.push_str(if *branch { "   " } else { "│  " });
+            prefix.push_str(if *branch { glyphs.blank } else { glyphs.branch });
         }
-        prefix.push_str(if is_last { "└─ " } else { "├─ " });
+        prefix.push_str(if is_last { glyphs.last } else { glyphs.tee });
     }
 
-    let icon = if e.is_dir() { "📁 " } else { "📄 " };
+    let icon = match e.kind {
+        EntryKind::Dir => glyphs.dir,
+        EntryKind::Entry => glyphs.entry,
+        EntryKind::Plain => glyphs.plain,
+    };
     let mut spans: Vec<Span<'static>> = Vec::with_capacity(4);
-    spans.push(Span::raw(prefix));
+    // Dim connectors so names stand out; the selected row keeps the list highlight
+    if selected {
+        spans.push(Span::raw(prefix));
+    } else {
+        spans.push(Span::styled(prefix, Style::default().fg(theme.tree)));
+    }
+    if app.selection.contains(&row.idx) {
+        spans.push(Span::styled(
+            glyphs.marked,
+            theme.highlight().add_modifier(Modifier::BOLD),
+        ));
+    }
     spans.push(Span::raw(icon.to_string()));
 
-    let name = e.display_name();
-    if filter_active && !filter.is_empty() {
-        let highlight = Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD);
-        spans.extend(highlight_matches(&name, filter, highlight));
+    let name = if branches.is_empty() {
+        sanitize_for_display(&e.store_key())
     } else {
-        spans.push(Span::raw(name));
-    }
+        sanitize_for_display(&e.display_name())
+    };
+    let filter = app.filter_mode.then_some(app.filter_input.as_str());
+    let name_spans = match filter.filter(|filter| !filter.is_empty()) {
+        Some(filter) => {
+            let highlight = theme.highlight().add_modifier(Modifier::BOLD);
+            let key = sanitize_for_display(&e.store_key());
+            highlight_matches(&name, &key, filter, highlight)
+        }
+        None => vec![Span::raw(name)],
+    };
+    let used: usize = spans.iter().map(Span::width).sum::<usize>() + usize::from(e.is_dir());
+    spans.extend(truncate_spans(
+        name_spans,
+        width.saturating_sub(used),
+        branches.is_empty(),
+    ));
 
     if e.is_dir() {
         spans.push(Span::raw("/".to_string()));
     }
 
-    ListItem::new(Line::from(spans))
+    Line::from(spans)
 }
 
-fn highlight_matches(name: &str, needle: &str, highlight: Style) -> Vec<Span<'static>> {
-    let mut spans = Vec::new();
-    let mut rest = name;
+/// Shorten `spans` to `max` characters with an ellipsis, cutting the end off, or the middle to
+/// keep both ends of a path. Each kept character keeps its style.
+fn truncate_spans(spans: Vec<Span<'static>>, max: usize, middle: bool) -> Vec<Span<'static>> {
+    let chars: Vec<(char, Style)> = spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+    if chars.len() <= max {
+        return spans;
+    }
+    let keep = max.saturating_sub(1);
+    let tail = if middle { keep / 2 } else { 0 };
+    let mut kept = chars[..keep - tail].to_vec();
+    kept.push(('…', Style::default()));
+    kept.extend_from_slice(&chars[chars.len() - tail..]);
 
-    while let Some(pos) = rest.find(needle) {
-        let (head, tail) = rest.split_at(pos);
-        if !head.is_empty() {
-            spans.push(Span::raw(head.to_owned()))
+    let mut truncated: Vec<Span<'static>> = Vec::new();
+    for (c, style) in kept {
+        match truncated.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => truncated.push(Span::styled(c.to_string(), style)),
         }
-        let (matched, remaining) = tail.split_at(needle.len());
-        spans.push(Span::styled(matched.to_owned(), highlight));
-        rest = remaining;
     }
+    truncated
+}
 
-    if !rest.is_empty() {
-        spans.push(Span::raw(rest.to_owned()));
+/// Append dimmed `text` at the right edge of a `width`-column row, unless it would not fit.
+fn push_right_aligned(line: &mut Line<'static>, text: String, width: usize, color: Color) {
+    let used = line.width() + text.chars().count();
+    // keep at least one space between the name and the appended text
+    if used < width {
+        line.spans.push(Span::raw(" ".repeat(width - used)));
+        line.spans
+            .push(Span::styled(text, Style::default().fg(color)));
+    }
+}
+
+/// Style the characters of `name` that the filter matched. A filter with a `/` is matched against
+/// the whole `key`, so only the part of the match that falls within the name is highlighted.
+fn highlight_matches(name: &str, key: &str, needle: &str, highlight: Style) -> Vec<Span<'static>> {
+    let positions = if needle.contains('/') {
+        let offset = key.chars().count().saturating_sub(name.chars().count());
+        fuzzy_match(key, needle).map(|(_, positions)| {
+            positions
+                .into_iter()
+                .filter_map(|pos| pos.checked_sub(offset))
+                .collect::<Vec<_>>()
+        })
+    } else {
+        fuzzy_match(name, needle).map(|(_, positions)| positions)
+    };
+    let Some(positions) = positions else {
+        return vec![Span::raw(name.to_owned())];
+    };
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+
+    // group consecutive chars that share a style into one span
+    for (i, c) in name.chars().enumerate() {
+        let matched = positions.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let text = std::mem::take(&mut run);
+            spans.push(if run_matched {
+                Span::styled(text, highlight)
+            } else {
+                Span::raw(text)
+            });
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(if run_matched {
+            Span::styled(run, highlight)
+        } else {
+            Span::raw(run)
+        });
     }
 
     spans
 }
 
+/// Mask a preview line: `key: value` lines after the first keep their key, all else is hidden.
+fn mask_line(index: usize, line: &str) -> String {
+    const MASK: &str = "••••••";
+    match parse_field(line) {
+        Some((key, _)) if index > 0 => format!("{}: {}", sanitize_for_display(key), MASK),
+        _ if line.trim().is_empty() => String::new(),
+        _ => MASK.to_string(),
+    }
+}
+
+/// Lay out an entry as aligned `key  value` rows, the password first.
+fn field_table(content: &str, masked: bool) -> String {
+    const MASK: &str = "••••••";
+    let (password, fields) = parse_entry(content);
+    let rows: Vec<(String, String)> = std::iter::once(("password", password))
+        .chain(fields)
+        .map(|(key, value)| {
+            let value = if masked && !value.is_empty() {
+                MASK.to_string()
+            } else {
+                sanitize_for_display(value)
+            };
+            (sanitize_for_display(key), value)
+        })
+        .collect();
+    let width = rows
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    rows.iter()
+        .map(|(key, value)| format!("{key:<width$}  {value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Draw `data` as a QR code using half blocks, two modules per character row. Light modules
+/// are drawn white on black so the code scans on dark and light terminal themes alike.
+fn render_qr(data: &str, max_width: u16) -> Vec<Line<'static>> {
+    const QUIET: usize = 2;
+    let code = match QrCode::new(data.as_bytes()) {
+        Ok(code) => code,
+        Err(err) => return vec![Line::from(format!("Cannot encode QR code: {}", err))],
+    };
+    let modules = code.width();
+    let size = modules + 2 * QUIET;
+    if size > usize::from(max_width) {
+        return vec![Line::from(format!(
+            "Preview too narrow for QR code (needs {} columns)",
+            size + 2
+        ))];
+    }
+    let colors = code.to_colors();
+    let light = |x: usize, y: usize| {
+        if x < QUIET || y < QUIET || x >= modules + QUIET || y >= modules + QUIET {
+            return true;
+        }
+        colors[(y - QUIET) * modules + (x - QUIET)] == qrcode::Color::Light
+    };
+    let style = Style::default().fg(Color::White).bg(Color::Black);
+    (0..size)
+        .step_by(2)
+        .map(|y| {
+            let row: String = (0..size)
+                .map(|x| {
+                    let top = light(x, y);
+                    let bottom = y + 1 < size && light(x, y + 1);
+                    match (top, bottom) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    }
+                })
+                .collect();
+            Line::from(Span::styled(row, style))
+        })
+        .collect()
+}
+
 fn list_state(app: &App) -> ratatui::widgets::ListState {
     let mut state = ratatui::widgets::ListState::default();
     let len = app.rows.len();
@@ -348,53 +947,204 @@ fn handle_key(app: &mut App, key: KeyEvent) -> Result<bool> {
         return Ok(redraw);
     }
 
+    if let Some(redraw) = handle_jump_key(app, key) {
+        return Ok(redraw);
+    }
+
+    if let Some(redraw) = handle_goto_key(app, key) {
+        return Ok(redraw);
+    }
+
+    let Some(action) = app.keymap.feed(key) else {
+        return Ok(false);
+    };
     let mut changed = false;
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Char('Q') => app.quit = true,
-        KeyCode::Down | KeyCode::Char('j') => {
-            if app.cursor + 1 < app.rows.len() {
-                app.cursor += 1;
-                changed = true;
-            }
+    match action {
+        Action::Quit => app.quit = true,
+        Action::Edit
+        | Action::EditLine
+        | Action::Add
+        | Action::Insert
+        | Action::AddTemplate
+        | Action::Rename
+        | Action::BulkRename
+        | Action::Copy
+        | Action::Delete
+        | Action::Undo
+        | Action::Regenerate
+        | Action::Pull
+        | Action::Push
+            if app.read_only =>
+        {
+            app.set_status(READ_ONLY_STATUS.to_string());
+            changed = true;
         }
-        KeyCode::Up | KeyCode::Char('k') => {
-            if app.cursor > 0 {
-                app.cursor -= 1;
-                changed = true;
-            }
+        Action::Down => changed = app.move_down(),
+        Action::Up => changed = app.move_up(),
+        Action::First => {
+            app.cursor = 0;
+            app.follow_selection();
+            changed = true;
         }
-        KeyCode::Enter => {
+        Action::Last => {
+            app.cursor = app.rows.len().saturating_sub(1);
+            app.follow_selection();
+            changed = true;
+        }
+        Action::HalfPageDown => {
+            app.move_cursor(app.half_page());
+            app.follow_selection();
+            changed = true;
+        }
+        Action::HalfPageUp => {
+            app.move_cursor(-app.half_page());
+            app.follow_selection();
+            changed = true;
+        }
+        Action::View if app.print_selected && app.selected_entry_path().is_some() => {
+            app.picked = app.selected_store_key();
+            app.quit = true;
+        }
+        Action::View => {
             if app.selected_entry_path().is_some() {
-                app.update_preview();
+                match app.config.enter_action {
+                    EnterAction::Preview => app.update_preview(),
+                    EnterAction::Yank => app.yank_selected(),
+                    EnterAction::ViewAndYank => {
+                        app.update_preview();
+                        app.yank_selected();
+                    }
+                }
             } else {
                 app.enter();
             }
             changed = true;
         }
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if app.selected_entry_path().is_some() {
-                app.update_preview_qr();
+        Action::PreviewDown | Action::PreviewUp if app.selected_preview_key().is_some() => {
+            let delta = if action == Action::PreviewDown {
+                PREVIEW_PAGE
+            } else {
+                -PREVIEW_PAGE
+            };
+            app.scroll_preview(delta);
+            changed = true;
+        }
+        Action::PreviewDown | Action::PreviewUp => {}
+        Action::PreviewLeft | Action::PreviewRight if !app.preview_wrap => {
+            let delta = if action == Action::PreviewRight {
+                PREVIEW_PAN
+            } else {
+                -PREVIEW_PAN
+            };
+            app.pan_preview(delta);
+            changed = true;
+        }
+        Action::PreviewLeft | Action::PreviewRight => {}
+        Action::Wrap => {
+            app.toggle_preview_wrap();
+            changed = true;
+        }
+        Action::ShrinkList | Action::GrowList => {
+            let step = if action == Action::GrowList {
+                LIST_WIDTH_STEP
+            } else {
+                -LIST_WIDTH_STEP
+            };
+            app.resize_list(step);
+            changed = true;
+        }
+        Action::Reveal if app.preview_key.is_some() => {
+            app.preview_masked = !app.preview_masked;
+            changed = true;
+        }
+        Action::Reveal => {}
+        Action::Qr if app.selected_entry_path().is_some() => {
+            app.update_preview_qr();
+            changed = true;
+        }
+        Action::Otp if app.selected_entry_path().is_some() => {
+            app.update_preview_otp();
+            changed = true;
+        }
+        Action::Qr | Action::Otp => {}
+        Action::Fields => {
+            app.toggle_fields();
+            changed = true;
+        }
+        Action::Recipients => {
+            app.open_recipients_modal();
+            changed = true;
+        }
+        Action::GitLog => {
+            app.open_git_log_modal();
+            changed = true;
+        }
+        Action::Messages => {
+            app.open_messages_modal();
+            changed = true;
+        }
+        Action::Diagnostics => {
+            app.open_diagnostics_modal();
+            changed = true;
+        }
+        Action::Flash => {
+            app.flash_password();
+            changed = true;
+        }
+        Action::Peek => {
+            app.peek_password();
+            changed = true;
+        }
+        Action::Select => {
+            app.toggle_selection();
+            changed = true;
+        }
+        Action::Regenerate => {
+            app.open_regenerate_modal();
+            changed = true;
+        }
+        Action::BulkRename => {
+            app.open_bulk_rename_modal();
+            changed = true;
+        }
+        Action::Pager => {
+            if let Some(rel) = app.selected_entry_path() {
+                app.pending = Some(PendingAction::Pager(rel));
                 changed = true;
             }
         }
-        KeyCode::Left | KeyCode::Char('h') => {
+        Action::Flat => {
+            app.toggle_flat();
+            changed = true;
+        }
+        Action::OpenUrl => {
+            app.open_url();
+            changed = true;
+        }
+        Action::Collapse => {
             if let Some(row) = app.rows.get(app.cursor) {
                 let entry = &app.entries[row.idx];
-                if entry.is_dir() {
-                    let relative = entry.path.strip_prefix(&app.cwd).unwrap_or(&entry.path);
-                    let key = path_to_store_key(relative);
-                    if app.expanded.contains(&key) {
-                        app.expanded.remove(&key);
-                        app.apply_filter();
-                        changed = true;
-                    }
+                let relative = entry.path.strip_prefix(&app.cwd).unwrap_or(&entry.path);
+                let key = path_to_store_key(relative);
+                if entry.is_dir() && app.expanded.contains(&key) {
+                    app.expanded.remove(&key);
+                    app.apply_filter();
+                    changed = true;
+                } else if app.config.cd_into_dirs && row.branches.len() == 1 {
+                    changed = app.parent_dir();
                 }
+            } else if app.config.cd_into_dirs {
+                changed = app.parent_dir();
             }
         }
-        KeyCode::Right | KeyCode::Char('l') => {
+        Action::Expand => {
             if let Some(row) = app.rows.get(app.cursor) {
                 let entry = &app.entries[row.idx];
-                if entry.is_dir() {
+                if entry.is_dir() && app.config.cd_into_dirs {
+                    let dir = entry.path.clone();
+                    app.change_dir(&dir);
+                    changed = true;
+                } else if entry.is_dir() {
                     let relative = entry.path.strip_prefix(&app.cwd).unwrap_or(&entry.path);
                     let key = path_to_store_key(relative);
                     if !app.expanded.contains(&key) {
@@ -405,48 +1155,202 @@ fn handle_key(app: &mut App, key: KeyEvent) -> Result<bool> {
                 }
             }
         }
-        KeyCode::Char('/') => {
-            app.filter_mode = true;
-            app.filter_input = app.filter.clone();
+        Action::ExpandAll => {
+            app.expand_all();
             changed = true;
         }
-        KeyCode::Esc => {
+        Action::CollapseAll => {
+            app.collapse_all();
+            changed = true;
+        }
+        Action::Sort => {
+            app.cycle_sort();
+            changed = true;
+        }
+        Action::Jump => {
+            app.start_jump();
+            changed = true;
+        }
+        Action::Goto => {
+            app.goto_input = Some(String::new());
+            changed = true;
+        }
+        Action::Filter => {
+            app.start_filter();
+            changed = true;
+        }
+        Action::ClearFilter => {
+            app.selection.clear();
+            app.grep = None;
             app.filter.clear();
             app.apply_filter();
             app.status = None;
             changed = true;
         }
-        KeyCode::Char('y') | KeyCode::Char('Y') => {
-            if let Some(rel) = app.selected_entry_path() {
-                if let Err(e) = app.backend.yank(&rel) {
-                    app.status = Some(e.to_string());
-                }
+        Action::Profiles => {
+            app.open_profile_modal();
+            changed = true;
+        }
+        Action::Grep => {
+            app.open_grep_modal();
+            changed = true;
+        }
+        Action::Stores => {
+            app.open_store_modal();
+            changed = true;
+        }
+        Action::CopyAll => {
+            app.yank_all_selected();
+            changed = true;
+        }
+        Action::CopyKey => {
+            app.copy_selected_key();
+            changed = true;
+        }
+        Action::CopyPath => {
+            app.copy_selected_path();
+            changed = true;
+        }
+        Action::Yank => {
+            if app.selected_entry_path().is_some() {
+                app.yank_selected();
                 changed = true;
             }
         }
-        KeyCode::Char('e') | KeyCode::Char('E') => {
+        Action::YankField => {
+            app.open_field_picker();
+            changed = true;
+        }
+        Action::YankUsername => {
+            app.yank_username();
+            changed = true;
+        }
+        Action::CopySequence => {
+            app.copy_next_in_sequence();
+            changed = true;
+        }
+        Action::Pull => {
+            app.pending = Some(PendingAction::GitPull);
+            changed = true;
+        }
+        Action::Push => {
+            app.pending = Some(PendingAction::GitPush);
+            changed = true;
+        }
+        Action::Edit => {
             if let Some(rel) = app.selected_entry_path() {
                 app.pending = Some(PendingAction::Edit(rel));
                 changed = true;
             }
         }
-        KeyCode::Char('r') | KeyCode::Char('R') => {
+        Action::EditLine => {
+            app.open_edit_line_modal();
+            changed = true;
+        }
+        Action::Rename => {
             app.open_rename_modal();
             changed = true;
         }
-        KeyCode::Char('a') | KeyCode::Char('A') => {
+        Action::Copy => {
+            app.open_copy_modal();
+            changed = true;
+        }
+        Action::Add => {
             app.open_add_modal();
             changed = true;
         }
-        KeyCode::Char('d') | KeyCode::Char('D') => {
+        Action::Insert => {
+            app.open_insert_modal();
+            changed = true;
+        }
+        Action::AddTemplate => {
+            app.open_template_modal();
+            changed = true;
+        }
+        Action::Delete => {
             app.open_delete_modal();
             changed = true;
         }
-        _ => {}
+        Action::Undo => {
+            app.pending = Some(PendingAction::Undo);
+            changed = true;
+        }
     }
     Ok(changed)
 }
 
+fn handle_mouse(app: &mut App, mouse: MouseEvent, area: Rect) -> bool {
+    let (header_area, list_area, preview_area) = screen_layout(area, app);
+    let at = (mouse.column, mouse.row);
+    let inside = |rect: Rect| rect.contains(ratatui::layout::Position { x: at.0, y: at.1 });
+
+    if let Some(Modal::Confirm { selected_ok, .. }) = &mut app.modal {
+        let MouseEventKind::Down(MouseButton::Left) = mouse.kind else {
+            return false;
+        };
+        let (_, buttons) = confirm_layout(modal_area(area));
+        if mouse.row != buttons.y || mouse.column < buttons.x {
+            return false;
+        }
+        let col = mouse.column - buttons.x;
+        let ok_width = OK_BUTTON.len() as u16;
+        let cancel_start = ok_width + BUTTON_GAP;
+        if col < ok_width {
+            *selected_ok = true;
+        } else if (cancel_start..cancel_start + CANCEL_BUTTON.len() as u16).contains(&col) {
+            *selected_ok = false;
+        } else {
+            return false;
+        }
+        if let Some(action) = app.submit_modal() {
+            app.pending = Some(action);
+        }
+        return true;
+    }
+    if app.modal.is_some() {
+        return false;
+    }
+
+    match mouse.kind {
+        MouseEventKind::ScrollDown if inside(preview_area) => app.scroll_preview(WHEEL_LINES),
+        MouseEventKind::ScrollUp if inside(preview_area) => app.scroll_preview(-WHEEL_LINES),
+        MouseEventKind::ScrollDown if app.cursor + 1 < app.rows.len() => app.cursor += 1,
+        MouseEventKind::ScrollUp if app.cursor > 0 => app.cursor -= 1,
+        MouseEventKind::Down(MouseButton::Left) if inside(header_area) => {
+            let col = mouse.column - header_area.x;
+            let Some((_, dir)) = breadcrumb_targets(app)
+                .into_iter()
+                .find(|(range, _)| range.contains(&col))
+            else {
+                return false;
+            };
+            if dir == app.cwd {
+                return false;
+            }
+            app.change_dir(&dir);
+        }
+        MouseEventKind::Down(MouseButton::Left) if inside(list_area) => {
+            let inner = list_area.inner(&ratatui::layout::Margin {
+                vertical: 1,
+                horizontal: 1,
+            });
+            if !inside(inner) {
+                return false;
+            }
+            // the list keeps the cursor on its last line once it scrolls past the first page
+            let height = usize::from(inner.height);
+            let offset = app.cursor.saturating_sub(height.saturating_sub(1));
+            let idx = offset + usize::from(mouse.row - inner.y);
+            if idx >= app.rows.len() {
+                return false;
+            }
+            app.cursor = idx;
+        }
+        _ => return false,
+    }
+    true
+}
+
 fn handle_modal_key(app: &mut App, key: KeyEvent) -> Result<bool> {
     let Some(_) = app.modal else {
         return Ok(false);
@@ -458,9 +1362,28 @@ fn handle_modal_key(app: &mut App, key: KeyEvent) -> Result<bool> {
     {
         let modal = app.modal.as_mut().expect("checked modal exists");
         match modal {
-            Modal::Input { buffer, .. } => match key.code {
+            Modal::Input { buffer, error, .. } => match key.code {
                 KeyCode::Esc => dismiss = true,
                 KeyCode::Enter => submit = true,
+                KeyCode::Backspace => {
+                    buffer.pop();
+                    *error = None;
+                }
+                KeyCode::Char(c)
+                    if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+                {
+                    buffer.push(c);
+                    *error = None;
+                }
+                _ => {}
+            },
+            Modal::Multiline { buffer, .. } => match key.code {
+                KeyCode::Esc => dismiss = true,
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    submit = true;
+                }
+                KeyCode::Enter => buffer.push('\n'),
+                KeyCode::Tab => buffer.push('\t'),
                 KeyCode::Backspace => {
                     buffer.pop();
                 }
@@ -479,11 +1402,29 @@ fn handle_modal_key(app: &mut App, key: KeyEvent) -> Result<bool> {
                 KeyCode::Enter => submit = true,
                 _ => {}
             },
+            Modal::Info { .. } => match key.code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => dismiss = true,
+                _ => {}
+            },
+            Modal::Flash { .. } => dismiss = true,
+            Modal::Picker {
+                items, selected, ..
+            } => match key.code {
+                KeyCode::Esc => dismiss = true,
+                KeyCode::Enter => submit = true,
+                KeyCode::Down | KeyCode::Char('j') if *selected + 1 < items.len() => {
+                    *selected += 1;
+                }
+                KeyCode::Up | KeyCode::Char('k') if *selected > 0 => {
+                    *selected -= 1;
+                }
+                _ => {}
+            },
         }
     }
 
     if dismiss {
-        app.modal = None;
+        app.close_modal();
         return Ok(true);
     }
 
@@ -509,11 +1450,9 @@ fn handle_filter_key(app: &mut App, key: KeyEvent) -> Option<bool> {
             app.filter_input.clear();
             app.apply_filter();
         }
-        KeyCode::Enter => {
-            app.filter = app.filter_input.clone();
-            app.filter_mode = false;
-            app.apply_filter();
-        }
+        KeyCode::Enter => app.submit_filter(),
+        KeyCode::Up => app.recall_filter(true),
+        KeyCode::Down => app.recall_filter(false),
         KeyCode::Backspace => {
             app.filter_input.pop();
         }
@@ -526,6 +1465,46 @@ fn handle_filter_key(app: &mut App, key: KeyEvent) -> Option<bool> {
     Some(true)
 }
 
+fn handle_jump_key(app: &mut App, key: KeyEvent) -> Option<bool> {
+    app.jump_prefix.as_ref()?;
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter => app.end_jump(),
+        KeyCode::Backspace => app.jump(None),
+        KeyCode::Char(c) if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT => {
+            app.jump(Some(c));
+        }
+        // anything else leaves jump mode and is handled as usual
+        _ => {
+            app.end_jump();
+            return None;
+        }
+    }
+
+    Some(true)
+}
+
+fn handle_goto_key(app: &mut App, key: KeyEvent) -> Option<bool> {
+    let input = app.goto_input.as_mut()?;
+
+    match key.code {
+        KeyCode::Esc => app.goto_input = None,
+        KeyCode::Enter => {
+            if let Ok(number) = input.parse() {
+                app.goto_row(number);
+            }
+            app.goto_input = None;
+        }
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+        _ => {}
+    }
+
+    Some(true)
+}
+
 fn centered_rect(
     percent_x: u16,
     percent_y: u16,
@@ -556,23 +1535,54 @@ fn centered_rect(
     })
 }
 
-fn suspend_and_run<F>(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, f: F) -> Result<()>
+/// The preview header rating the password: a four-cell meter, the verdict, length and classes.
+fn strength_line(app: &App, strength: Strength) -> Line<'static> {
+    let theme = &app.theme;
+    let color = match strength.score {
+        0 | 1 => theme.error,
+        2 => theme.highlight,
+        _ => Color::Reset,
+    };
+    let score = usize::from(strength.score);
+    let meter = app.glyphs.meter_full.repeat(score) + &app.glyphs.meter_empty.repeat(4 - score);
+    Line::from(vec![
+        Span::styled(
+            format!("{} {}", meter, strength.label()),
+            Style::default().fg(color),
+        ),
+        Span::styled(
+            format!(" · {} chars · {}", strength.length, strength.classes()),
+            Style::default().fg(theme.dim),
+        ),
+    ])
+}
+
+fn suspend_and_run<F>(terminal: &mut Tui, alt_screen: bool, f: F) -> Result<()>
 where
     F: FnOnce() -> Result<()>,
 {
     // leave raw mode and alt screen
     disable_raw_mode()?;
-    crossterm::execute!(
-        terminal.backend_mut(),
-        crossterm::terminal::LeaveAlternateScreen,
-        crossterm::cursor::Show
-    )?;
+    crossterm::execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    if alt_screen {
+        crossterm::execute!(
+            terminal.backend_mut(),
+            crossterm::terminal::LeaveAlternateScreen
+        )?;
+    }
+    crossterm::execute!(terminal.backend_mut(), crossterm::cursor::Show)?;
     let result = f();
     // re-enter
+    if alt_screen {
+        crossterm::execute!(
+            terminal.backend_mut(),
+            crossterm::terminal::EnterAlternateScreen
+        )?;
+    }
     crossterm::execute!(
         terminal.backend_mut(),
-        crossterm::terminal::EnterAlternateScreen,
-        crossterm::cursor::Hide
+        crossterm::cursor::Hide,
+        EnableMouseCapture
     )?;
     enable_raw_mode()?;
     // ensure a clean screen on resume
@@ -581,10 +1591,340 @@ where
 }
 
 fn run_action(app: &mut App, action: PendingAction) -> Result<()> {
-    match action {
+    let record = match &action {
+        PendingAction::Edit(rel) => Some(("edit", rel.clone())),
+        PendingAction::Add(rel) => Some(("add", rel.clone())),
+        PendingAction::Insert { entry, .. } => Some(("insert", entry.clone())),
+        PendingAction::EditLine { entry, .. } => Some(("edit", entry.clone())),
+        PendingAction::Delete => app.selected_store_key().map(|key| ("delete", key)),
+        PendingAction::Rename { from, to } => Some(("rename", format!("{} -> {}", from, to))),
+        PendingAction::Copy { from, to } => Some(("copy", format!("{} -> {}", from, to))),
+        PendingAction::Pager(rel) => Some(("show", rel.clone())),
+        PendingAction::YankField { entry, field } => {
+            Some(("yank", format!("{} ({})", entry, field)))
+        }
+        _ => None,
+    };
+    let result = match action {
         PendingAction::Edit(rel) => app.backend.edit(&rel),
         PendingAction::Add(path) => app.backend.add(&path),
+        PendingAction::Insert { entry, content } => app.insert(&entry, content),
+        PendingAction::EditLine { entry, line } => app.edit_line(&entry, line),
         PendingAction::Delete => app.delete_selected(),
-        PendingAction::Rename { from, to } => app.backend.mv(&from, &to),
+        PendingAction::Rename { from, to } => app.rename(&from, &to),
+        PendingAction::Copy { from, to } => app.copy(&from, &to),
+        PendingAction::DeleteMany(keys) => app.delete_many(&keys),
+        PendingAction::MoveMany(moves) => app.move_many(&moves),
+        PendingAction::Undo => app.undo(),
+        PendingAction::Prune(dir) => app.prune_empty_parents(&dir),
+        PendingAction::Pager(rel) => app.backend.page(&rel),
+        PendingAction::Regenerate(rel) => app.regenerate(&rel),
+        PendingAction::SwitchProfile(name) => app.switch_profile(&name),
+        PendingAction::SwitchStore(store) => app.switch_store(&store),
+        PendingAction::Grep(pattern) => app.grep(&pattern),
+        PendingAction::YankField { entry, field } => app.yank_field(&entry, &field),
+        PendingAction::GitPull => app.git_sync(&["pull"]),
+        PendingAction::GitPush => app.git_sync(&["push"]),
+    };
+    if let Some((action, target)) = record {
+        app.audit(action, &target, &result);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_timeout_follows_what_is_running() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        assert_eq!(poll_timeout(&app), IDLE_POLL);
+
+        app.clipboard_expires_at = Some(std::time::Instant::now() + Duration::from_secs(45));
+        assert_eq!(poll_timeout(&app), TICK_POLL);
+
+        app.preview_pending_since = Some(std::time::Instant::now());
+        assert_eq!(poll_timeout(&app), PREVIEW_POLL);
+
+        let config = crate::config::Config {
+            watch_store: true,
+            ..Default::default()
+        };
+        let app = App::new_with_store(Some(tmp.path().to_path_buf()), config)?;
+        assert_eq!(poll_timeout(&app), WATCH_POLL);
+        Ok(())
+    }
+
+    #[test]
+    fn masking_keeps_field_names_only() {
+        assert_eq!(mask_line(0, "user: hunter2"), "••••••");
+        assert_eq!(mask_line(1, "user: bob"), "user: ••••••");
+        assert_eq!(mask_line(2, "https://example.com"), "••••••");
+        assert_eq!(mask_line(3, ""), "");
+    }
+
+    #[test]
+    fn path_filters_highlight_the_part_in_the_name() {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        let spans = highlight_matches("aws", "work/aws", "work/aw", style);
+        assert_eq!(spans, vec![Span::styled("aw", style), Span::raw("s")]);
+        // the match lies entirely in the parent directory
+        let spans = highlight_matches("aws", "work/aws", "wo/", style);
+        assert_eq!(spans, vec![Span::raw("aws")]);
+    }
+
+    #[test]
+    fn long_names_are_cut_at_the_end_and_paths_in_the_middle() {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        let name = vec![Span::styled("very", style), Span::raw("-long-name")];
+        assert_eq!(
+            truncate_spans(name.clone(), 8, false),
+            vec![Span::styled("very", style), Span::raw("-lo…")]
+        );
+        assert_eq!(truncate_spans(name.clone(), 14, false), name);
+        let path = vec![Span::raw("work/aws/eu-west/prod")];
+        assert_eq!(
+            truncate_spans(path, 11, true),
+            vec![Span::raw("work/…/prod")]
+        );
+    }
+
+    #[test]
+    fn field_table_aligns_values_and_masks_them() {
+        let content = "hunter2\nuser: bob\nnote without a key\n";
+        assert_eq!(
+            field_table(content, false),
+            "password  hunter2\nuser      bob\n          note without a key"
+        );
+        assert_eq!(
+            field_table(content, true),
+            "password  ••••••\nuser      ••••••\n          ••••••"
+        );
+    }
+
+    #[test]
+    fn qr_uses_half_blocks_and_reports_when_too_narrow() {
+        // "hunter2" fits a version 1 code: 21 modules plus a 2-module quiet zone on each side
+        let lines = render_qr("hunter2", 80);
+        assert_eq!(lines.len(), 13);
+        assert!(lines.iter().all(|line| line.width() == 25));
+
+        let narrow = render_qr("hunter2", 20);
+        assert_eq!(narrow.len(), 1);
+        assert!(narrow[0].to_string().contains("too narrow"));
+    }
+
+    #[test]
+    fn read_only_mode_refuses_changes_but_still_moves() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        std::fs::write(tmp.path().join("a.gpg"), b"dummy")?;
+        std::fs::write(tmp.path().join("b.gpg"), b"dummy")?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        app.apply_filter();
+        app.read_only = true;
+
+        let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        for c in ['e', 'd', 'r', 'a', 'P'] {
+            app.status = None;
+            handle_key(&mut app, press(c))?;
+            assert!(app.modal.is_none() && app.pending.is_none(), "{c}");
+            assert_eq!(app.status.as_deref(), Some(READ_ONLY_STATUS));
+        }
+        handle_key(&mut app, press('j'))?;
+        assert_eq!(app.cursor, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn preview_follows_jumps_to_the_ends_and_by_half_pages() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        for name in ["a", "b", "c", "d", "e"] {
+            std::fs::write(tmp.path().join(format!("{name}.gpg")), b"dummy")?;
+        }
+        let config = crate::config::Config {
+            backend: Some("true".into()),
+            ..Default::default()
+        };
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), config)?;
+        app.apply_filter();
+        app.list_height = 4;
+
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT),
+        )?;
+        assert_eq!(app.preview_key.as_deref(), Some("e"));
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE),
+        )?;
+        assert_eq!(app.preview_key.as_deref(), Some("a"));
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+        )?;
+        assert_eq!(app.preview_key.as_deref(), Some("c"));
+        Ok(())
+    }
+
+    #[test]
+    fn print_selected_picks_entries_and_enters_directories() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        std::fs::create_dir_all(tmp.path().join("web"))?;
+        std::fs::write(tmp.path().join("web/site.gpg"), b"dummy")?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        app.apply_filter();
+        app.print_selected = true;
+
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        app.select_key("web");
+        handle_key(&mut app, enter)?;
+        assert!(!app.quit && app.picked.is_none());
+        app.select_key("web/site");
+        handle_key(&mut app, enter)?;
+        assert!(app.quit);
+        assert_eq!(app.picked.as_deref(), Some("web/site"));
+        assert!(app.preview_key.is_none() && app.preview_loading.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn up_and_down_recall_submitted_filters() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for filter in ["mail", "work", "mail"] {
+            handle_key(&mut app, press(KeyCode::Char('/')))?;
+            app.filter_input = filter.into();
+            handle_key(&mut app, press(KeyCode::Enter))?;
+        }
+        assert_eq!(app.filter_history, ["work", "mail"]);
+
+        handle_key(&mut app, press(KeyCode::Char('/')))?;
+        app.filter_input = "dra".into();
+        handle_key(&mut app, press(KeyCode::Up))?;
+        assert_eq!(app.filter_input, "mail");
+        handle_key(&mut app, press(KeyCode::Up))?;
+        handle_key(&mut app, press(KeyCode::Up))?;
+        assert_eq!(app.filter_input, "work");
+        handle_key(&mut app, press(KeyCode::Down))?;
+        assert_eq!(app.filter_input, "mail");
+        handle_key(&mut app, press(KeyCode::Down))?;
+        assert_eq!(app.filter_input, "dra");
+        Ok(())
+    }
+
+    #[test]
+    fn modals_survive_tiny_terminals() -> Result<()> {
+        use ratatui::backend::TestBackend;
+
+        let tmp = tempfile::tempdir()?;
+        std::fs::write(tmp.path().join("a.gpg"), b"dummy")?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        app.apply_filter();
+        app.open_delete_modal();
+        for (width, height) in [(1, 1), (12, 2), (24, 5), (80, 24)] {
+            let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+            terminal.draw(|f| draw_ui(f, &mut app))?;
+        }
+
+        // too small for a centered dialog, so it takes the whole screen and the buttons still show
+        let mut terminal = Terminal::new(TestBackend::new(30, 8))?;
+        terminal.draw(|f| draw_ui(f, &mut app))?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains(OK_BUTTON), "{screen}");
+        Ok(())
+    }
+
+    #[test]
+    fn breadcrumb_segments_lead_to_their_directories() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        std::fs::create_dir_all(tmp.path().join("work/aws"))?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        app.set_cwd(std::path::Path::new("work/aws"));
+
+        // "pass-tui  " is 10 columns, then "work" and "aws" separated by '/'
+        let targets = breadcrumb_targets(&app);
+        assert_eq!(targets[0], (0..10, PathBuf::new()));
+        assert_eq!(targets[1], (10..14, PathBuf::from("work")));
+        assert_eq!(targets[2], (15..18, PathBuf::from("work/aws")));
+        Ok(())
+    }
+
+    fn click(column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn clicks_on_a_scrolled_list_pick_the_row_shown() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        for n in 0..40 {
+            std::fs::write(tmp.path().join(format!("e{n:02}.gpg")), b"dummy")?;
+        }
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        app.apply_filter();
+        let area = Rect::new(0, 0, 80, 24);
+        let (_, list_area, _) = screen_layout(area, &app);
+        let inner = list_area.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let height = usize::from(inner.height);
+
+        // scrolled so the cursor is on the last visible line; the top line shows an earlier row
+        app.cursor = 30;
+        assert!(handle_mouse(&mut app, click(inner.x, inner.y), area));
+        assert_eq!(app.cursor, 30 + 1 - height);
+        assert!(handle_mouse(
+            &mut app,
+            click(inner.x + 2, inner.y + 3),
+            area
+        ));
+        assert_eq!(app.cursor, 3);
+        // the border is not a row
+        assert!(!handle_mouse(
+            &mut app,
+            click(list_area.x, inner.y + 1),
+            area
+        ));
+        assert_eq!(app.cursor, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn clicks_on_confirm_buttons_answer_the_dialog() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        std::fs::write(tmp.path().join("a.gpg"), b"dummy")?;
+        let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Default::default())?;
+        app.apply_filter();
+        let area = Rect::new(0, 0, 80, 24);
+        let (_, buttons) = confirm_layout(modal_area(area));
+        let cancel = buttons.x + OK_BUTTON.len() as u16 + BUTTON_GAP;
+
+        app.open_delete_modal();
+        // between the buttons
+        assert!(!handle_mouse(&mut app, click(cancel - 1, buttons.y), area));
+        assert!(app.modal.is_some());
+        assert!(handle_mouse(&mut app, click(cancel, buttons.y), area));
+        assert!(app.modal.is_none() && app.pending.is_none());
+
+        app.open_delete_modal();
+        assert!(handle_mouse(&mut app, click(buttons.x, buttons.y), area));
+        assert!(app.modal.is_none());
+        assert!(matches!(app.pending, Some(PendingAction::Delete)));
+        Ok(())
     }
 }
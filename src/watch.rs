@@ -0,0 +1,154 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+/// Shortest interval between checks of the store, however low `watch_interval_ms` is set
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Quiet time after the last change before a refresh is signalled, so that a burst of changes
+/// (a git checkout, a directory of imports) causes a single refresh
+const SETTLE_TIME: Duration = Duration::from_millis(700);
+
+/// Notices entries being added, removed or renamed in the store by other programs.
+///
+/// A background thread polls the modification times of the store's directories, which change
+/// whenever an entry inside them is created, deleted or renamed. Polling costs a walk of the
+/// directories per interval, which is why watching is opt-in and the interval configurable, but
+/// needs no platform-specific notification API. The thread stops when the watcher is dropped.
+pub struct StoreWatcher {
+    changes: Receiver<()>,
+    stop: Arc<AtomicBool>,
+    resync: Arc<AtomicBool>,
+}
+
+impl StoreWatcher {
+    /// Watch `root`, checking it every `interval`.
+    pub fn spawn(root: PathBuf, interval: Duration) -> Self {
+        Self::with_timing(root, interval.max(MIN_POLL_INTERVAL), SETTLE_TIME)
+    }
+
+    fn with_timing(root: PathBuf, interval: Duration, settle: Duration) -> Self {
+        let (tx, changes) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let resync = Arc::new(AtomicBool::new(false));
+        let resync_requested = Arc::clone(&resync);
+        thread::spawn(move || {
+            let mut last = fingerprint(&root);
+            let mut changed_at: Option<Instant> = None;
+            while !stopped.load(Ordering::Relaxed) {
+                thread::sleep(interval);
+                if resync_requested.swap(false, Ordering::Relaxed) {
+                    last = fingerprint(&root);
+                    changed_at = None;
+                    continue;
+                }
+                let current = fingerprint(&root);
+                if current != last {
+                    last = current;
+                    changed_at = Some(Instant::now());
+                } else if changed_at.is_some_and(|at| at.elapsed() >= settle) {
+                    changed_at = None;
+                    if tx.send(()).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            changes,
+            stop,
+            resync,
+        }
+    }
+
+    /// Whether the store changed since the last call.
+    pub fn changed(&self) -> bool {
+        self.changes.try_iter().count() > 0
+    }
+
+    /// Take the store as it is now as the baseline, forgetting changes seen so far. Called
+    /// after the list was re-indexed anyway, e.g. following pass-tui's own edits.
+    pub fn resync(&self) {
+        self.resync.store(true, Ordering::Relaxed);
+        self.changes.try_iter().for_each(drop);
+    }
+}
+
+impl Drop for StoreWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Hash of every directory's path and modification time below `root`, `.git` excluded.
+fn fingerprint(root: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let dirs = WalkDir::new(root)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir());
+    for dir in dirs {
+        dir.path().hash(&mut hasher);
+        if let Some(modified) = dir.metadata().ok().and_then(|meta| meta.modified().ok()) {
+            modified.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn a_burst_of_changes_is_reported_once() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        fs::create_dir_all(tmp.path().join("web"))?;
+        let watcher = StoreWatcher::with_timing(
+            tmp.path().to_path_buf(),
+            Duration::from_millis(20),
+            Duration::from_millis(100),
+        );
+        thread::sleep(Duration::from_millis(50));
+        assert!(!watcher.changed());
+
+        for name in ["a", "b", "c"] {
+            fs::write(tmp.path().join(format!("web/{name}.gpg")), b"dummy")?;
+            thread::sleep(Duration::from_millis(30));
+        }
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !watcher.changed() {
+            assert!(Instant::now() < deadline, "change was not noticed");
+            thread::sleep(Duration::from_millis(20));
+        }
+        thread::sleep(Duration::from_millis(200));
+        assert!(!watcher.changed());
+        Ok(())
+    }
+
+    #[test]
+    fn changes_before_a_resync_are_not_reported() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let watcher = StoreWatcher::with_timing(
+            tmp.path().to_path_buf(),
+            Duration::from_millis(20),
+            Duration::from_millis(100),
+        );
+        thread::sleep(Duration::from_millis(50));
+        fs::create_dir_all(tmp.path().join("web"))?;
+        watcher.resync();
+        thread::sleep(Duration::from_millis(300));
+        assert!(!watcher.changed());
+        Ok(())
+    }
+}
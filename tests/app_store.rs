@@ -0,0 +1,1174 @@
+use assert_fs::TempDir;
+use pass_tui::app::App;
+use pass_tui::config::Config;
+use std::fs;
+use std::os::unix::fs::symlink;
+
+/// Store keys of the listed rows, top to bottom
+fn row_keys(app: &App) -> Vec<String> {
+    app.rows
+        .iter()
+        .map(|row| app.entries[row.idx].store_key())
+        .collect()
+}
+
+#[test]
+fn symlinked_store_root_is_canonicalized() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    let real = tmp.path().join("real-store");
+    fs::create_dir_all(real.join("work"))?;
+    fs::write(real.join("work/aws.gpg"), b"dummy")?;
+    let link = tmp.path().join("linked-store");
+    symlink(&real, &link)?;
+
+    let mut app = App::new_with_store(Some(link), Config::default())?;
+    assert_eq!(app.store_dir, real.canonicalize()?);
+
+    app.expanded.insert("work".into());
+    app.apply_filter();
+    assert_eq!(row_keys(&app), ["work", "work/aws"]);
+    Ok(())
+}
+
+#[test]
+fn profiles_select_store_and_start_directory() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+    use std::path::PathBuf;
+
+    let tmp = TempDir::new()?;
+    let personal = tmp.path().join("personal");
+    let work = tmp.path().join("work");
+    fs::create_dir_all(personal.join("mail"))?;
+    fs::write(personal.join("mail/home.gpg"), b"dummy")?;
+    fs::create_dir_all(work.join("aws"))?;
+    fs::write(work.join("aws/prod.gpg"), b"dummy")?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "personal".into(),
+        Profile {
+            store: Some(personal.clone()),
+            ..Profile::default()
+        },
+    );
+    config.profiles.insert(
+        "work".into(),
+        Profile {
+            store: Some(work.clone()),
+            cwd: Some(PathBuf::from("aws")),
+            ..Profile::default()
+        },
+    );
+
+    assert!(App::new_with_profile(None, Some("missing"), config.clone()).is_err());
+
+    let mut app = App::new_with_profile(None, Some("personal"), config)?;
+    assert_eq!(app.store_dir, personal.canonicalize()?);
+    assert_eq!(app.profile.as_deref(), Some("personal"));
+
+    app.switch_profile("work")?;
+    assert_eq!(app.store_dir, work.canonicalize()?);
+    assert_eq!(app.cwd, PathBuf::from("aws"));
+    assert_eq!(row_keys(&app), ["aws/prod"]);
+    Ok(())
+}
+
+#[test]
+fn start_directory_must_exist_in_the_store() -> anyhow::Result<()> {
+    use std::path::{Path, PathBuf};
+
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("work/aws"))?;
+    fs::write(tmp.path().join("work/aws/prod.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("mail.gpg"), b"dummy")?;
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.set_cwd(Path::new("work/aws/"));
+    assert_eq!(app.cwd, PathBuf::from("work/aws"));
+
+    app.set_cwd(Path::new("work/gcp"));
+    assert_eq!(app.cwd, PathBuf::new());
+    assert_eq!(
+        app.status.as_deref(),
+        Some("Directory 'work/gcp' not found in store; showing root")
+    );
+    Ok(())
+}
+
+#[test]
+fn dry_run_keeps_empty_directories() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("old/empty"))?;
+    fs::write(tmp.path().join("mail.gpg"), b"dummy")?;
+
+    let config = Config {
+        dry_run: true,
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), config)?;
+    app.prune_empty_parents("old/empty")?;
+    assert!(tmp.path().join("old/empty").is_dir());
+    assert_eq!(
+        app.status.as_deref(),
+        Some("Dry run: no directories removed")
+    );
+    Ok(())
+}
+
+#[test]
+fn switching_stores_reindexes_from_the_root() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    let personal = tmp.path().join("personal");
+    let work = tmp.path().join("work");
+    fs::create_dir_all(personal.join("mail"))?;
+    fs::write(personal.join("mail/home.gpg"), b"dummy")?;
+    fs::create_dir_all(work.join("aws"))?;
+    fs::write(work.join("aws/prod.gpg"), b"dummy")?;
+
+    let mut app = App::new_with_store(Some(personal.clone()), Config::default())?;
+    app.stores = vec![personal, work.clone()];
+    app.expanded.insert("mail".into());
+    app.apply_filter();
+    app.cursor = 1;
+
+    app.switch_store(&work)?;
+    assert_eq!(app.store_dir, work.canonicalize()?);
+    assert_eq!(app.store_name(), "work");
+    assert_eq!(app.cursor, 0);
+    assert!(!app.expanded.contains("mail"));
+    assert_eq!(row_keys(&app), ["aws"]);
+    Ok(())
+}
+
+#[test]
+fn deleting_a_directory_counts_entries_and_defaults_to_cancel() -> anyhow::Result<()> {
+    use pass_tui::app::Modal;
+
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("work/aws"))?;
+    fs::write(tmp.path().join("work/aws/prod.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("work/vpn.gpg"), b"dummy")?;
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    app.cursor = 0;
+    app.open_delete_modal();
+    match &app.modal {
+        Some(Modal::Confirm {
+            message,
+            selected_ok,
+            ..
+        }) => {
+            assert_eq!(
+                message,
+                "Delete directory 'work' and its 2 entries? This cannot be undone."
+            );
+            assert!(!selected_ok);
+        }
+        other => panic!("expected a confirm modal, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn jump_moves_to_next_row_with_prefix_and_wraps() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    for name in ["alpha", "beta", "bravo", "charlie"] {
+        fs::write(tmp.path().join(format!("{name}.gpg")), b"dummy")?;
+    }
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    app.cursor = 3;
+    app.start_jump();
+    app.jump(Some('B'));
+    assert_eq!(app.cursor, 1);
+    app.jump(Some('r'));
+    assert_eq!(app.cursor, 2);
+    app.jump(None);
+    app.jump(Some('e'));
+    assert_eq!(app.cursor, 1);
+    assert_eq!(app.jump_prefix.as_deref(), Some("be"));
+    Ok(())
+}
+
+#[test]
+fn preview_ignores_results_for_entries_no_longer_selected() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join("fast.gpg"), b"dummy")?;
+    fs::write(store.join("slow.gpg"), b"dummy")?;
+    let script = tmp.path().join("fake-pass");
+    fs::write(
+        &script,
+        "#!/bin/sh\nif [ \"$1\" = slow ]; then sleep 1; fi\necho \"secret for $1\"\n",
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "fake".into(),
+        Profile {
+            store: Some(store),
+            backend: Some(script.display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let mut app = App::new_with_profile(None, Some("fake"), config)?;
+    app.apply_filter();
+    app.cursor = 1;
+    app.update_preview();
+    assert!(app.preview_loading.is_some());
+    app.cursor = 0;
+    app.update_preview();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.preview_loading.is_some() && Instant::now() < deadline {
+        app.poll_preview();
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(app.preview_text.trim(), "secret for fast");
+
+    // the slow result arriving afterwards must not replace it
+    std::thread::sleep(Duration::from_millis(1200));
+    assert!(!app.poll_preview());
+    assert_eq!(app.preview_text.trim(), "secret for fast");
+    Ok(())
+}
+
+#[test]
+fn preview_waits_for_the_cursor_to_rest() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+    use std::time::Duration;
+
+    let tmp = TempDir::new()?;
+    for name in ["alpha", "beta"] {
+        fs::write(tmp.path().join(format!("{name}.gpg")), b"dummy")?;
+    }
+    let mut config = Config {
+        preview_delay_ms: Some(50),
+        ..Config::default()
+    };
+    config.profiles.insert(
+        "quiet".into(),
+        Profile {
+            store: Some(tmp.path().to_path_buf()),
+            backend: Some("true".into()),
+            ..Profile::default()
+        },
+    );
+    let mut app = App::new_with_profile(None, Some("quiet"), config)?;
+    app.apply_filter();
+
+    assert!(!app.debounce_preview());
+    std::thread::sleep(Duration::from_millis(30));
+    app.cursor = 1;
+    assert!(!app.debounce_preview());
+    std::thread::sleep(Duration::from_millis(30));
+    // moving restarted the wait, so beta has not rested long enough yet
+    assert!(!app.debounce_preview());
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(app.debounce_preview());
+    assert_eq!(app.preview_key.as_deref(), Some("beta"));
+    Ok(())
+}
+
+#[test]
+fn changing_directory_scopes_the_list_and_goes_back_up() -> anyhow::Result<()> {
+    use std::path::Path;
+
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("work/aws"))?;
+    fs::write(tmp.path().join("work/aws/prod.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("work/vpn.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("mail.gpg"), b"dummy")?;
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    app.cursor = 1;
+    app.change_dir(Path::new("work"));
+    assert_eq!(app.cursor, 0);
+    assert_eq!(row_keys(&app), ["work/aws", "work/vpn"]);
+    app.cursor = 1;
+    assert_eq!(app.selected_entry_path().as_deref(), Some("work/vpn"));
+
+    assert!(app.parent_dir());
+    assert_eq!(app.cwd, Path::new(""));
+    assert_eq!(app.rows.len(), 2);
+    assert!(!app.parent_dir());
+    Ok(())
+}
+
+#[test]
+fn recent_sort_orders_entries_by_mtime_after_directories() -> anyhow::Result<()> {
+    use pass_tui::app::SortMode;
+    use std::time::{Duration, SystemTime};
+
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("zeta"))?;
+    let now = SystemTime::now();
+    for (name, days) in [("alpha", 30), ("beta", 1), ("gamma", 7)] {
+        let path = tmp.path().join(format!("{name}.gpg"));
+        fs::write(&path, b"dummy")?;
+        let file = fs::File::options().write(true).open(&path)?;
+        file.set_modified(now - Duration::from_secs(days * 86_400))?;
+    }
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    app.cycle_sort();
+    assert_eq!(app.sort_mode, SortMode::Recent);
+    assert_eq!(row_keys(&app), ["zeta", "beta", "gamma", "alpha"]);
+    Ok(())
+}
+
+#[test]
+fn peek_shows_the_password_without_touching_the_preview() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join("site.gpg"), b"dummy")?;
+    let script = tmp.path().join("fake-pass");
+    fs::write(&script, "#!/bin/sh\nprintf 'hunter2\\nuser: bob\\n'\n")?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let mut config = Config {
+        flash_seconds: 0,
+        ..Config::default()
+    };
+    config.profiles.insert(
+        "fake".into(),
+        Profile {
+            store: Some(store),
+            backend: Some(script.display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let mut app = App::new_with_profile(None, Some("fake"), config)?;
+    app.apply_filter();
+    app.peek_password();
+    assert_eq!(app.peek.as_deref(), Some("hunter2"));
+    assert!(app.preview_key.is_none() && app.preview_text.is_empty());
+
+    assert!(app.expire_peek());
+    assert!(app.peek.is_none());
+    Ok(())
+}
+
+#[test]
+fn entries_differing_only_by_case_are_reported_at_startup() -> anyhow::Result<()> {
+    use pass_tui::keymap::KeySpec;
+
+    let tmp = TempDir::new()?;
+    fs::write(tmp.path().join("GitHub.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("github.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("mail.gpg"), b"dummy")?;
+
+    let keys = [("bogus".to_string(), KeySpec::One("x".into()))];
+    let config = Config {
+        keys: keys.into_iter().collect(),
+        ..Config::default()
+    };
+    let app = App::new_with_store(Some(tmp.path().to_path_buf()), config)?;
+    // shown together with other startup problems, here an invalid key binding
+    let status = app.status.clone().unwrap_or_default();
+    assert!(
+        status.contains("Entries differ only by case: GitHub / github"),
+        "{status}"
+    );
+    assert!(status.contains("bogus"), "{status}");
+    Ok(())
+}
+
+#[test]
+fn message_log_keeps_the_latest_statuses() -> anyhow::Result<()> {
+    use pass_tui::app::Modal;
+
+    let tmp = TempDir::new()?;
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.messages.clear();
+    for i in 0..60 {
+        app.set_status(format!("message {i}"));
+    }
+    assert_eq!(app.messages.len(), 50);
+    assert_eq!(app.messages[0].1, "message 10");
+
+    app.open_messages_modal();
+    match &app.modal {
+        Some(Modal::Info { lines, .. }) => assert!(lines[0].ends_with("message 59"), "{lines:?}"),
+        other => panic!("expected the message log, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn invalid_add_path_keeps_the_modal_open_with_an_error() -> anyhow::Result<()> {
+    use pass_tui::app::{Modal, PendingAction};
+
+    let tmp = TempDir::new()?;
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.open_add_modal();
+    if let Some(Modal::Input { buffer, .. }) = &mut app.modal {
+        *buffer = "../outside".into();
+    }
+    assert!(app.submit_modal().is_none());
+    match &app.modal {
+        Some(Modal::Input { error, .. }) => assert!(error.is_some()),
+        other => panic!("expected the add modal to stay open, got {other:?}"),
+    }
+
+    // pass would have nobody to encrypt to until a .gpg-id covers the path
+    if let Some(Modal::Input { buffer, .. }) = &mut app.modal {
+        *buffer = "web//github".into();
+    }
+    assert!(app.submit_modal().is_none());
+    match &app.modal {
+        Some(Modal::Input { error, .. }) => {
+            assert!(error.as_deref().unwrap_or_default().contains("pass init"))
+        }
+        other => panic!("expected the add modal to stay open, got {other:?}"),
+    }
+    fs::create_dir_all(tmp.path().join("web"))?;
+    fs::write(tmp.path().join("web/.gpg-id"), "me@example.com\n")?;
+    match app.submit_modal() {
+        Some(PendingAction::Add(entry)) => assert_eq!(entry, "web/github"),
+        other => panic!("expected an add, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn adding_over_an_existing_entry_offers_to_edit_it() -> anyhow::Result<()> {
+    use pass_tui::app::{Modal, PendingAction};
+
+    let tmp = TempDir::new()?;
+    fs::write(tmp.path().join(".gpg-id"), "me@example.com\n")?;
+    fs::create_dir_all(tmp.path().join("web"))?;
+    fs::write(tmp.path().join("web/example.com.gpg"), b"dummy")?;
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    let add = |app: &mut App, path: &str| {
+        app.open_add_modal();
+        if let Some(Modal::Input { buffer, .. }) = &mut app.modal {
+            *buffer = path.into();
+        }
+        app.submit_modal()
+    };
+
+    assert!(add(&mut app, "web/example.com").is_none());
+    match &mut app.modal {
+        Some(Modal::Confirm { selected_ok, .. }) => *selected_ok = true,
+        other => panic!("expected a confirmation, got {other:?}"),
+    }
+    match app.submit_modal() {
+        Some(PendingAction::Edit(entry)) => assert_eq!(entry, "web/example.com"),
+        other => panic!("expected an edit, got {other:?}"),
+    }
+
+    // declining adds nothing
+    assert!(add(&mut app, "web/example.com").is_none());
+    assert!(app.submit_modal().is_none());
+
+    assert!(add(&mut app, "web").is_none());
+    assert!(app.modal.is_none());
+    assert!(app
+        .status
+        .as_deref()
+        .unwrap_or_default()
+        .contains("directory"));
+
+    match add(&mut app, "web/example.org") {
+        Some(PendingAction::Add(entry)) => assert_eq!(entry, "web/example.org"),
+        other => panic!("expected an add, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn backend_receives_the_canonical_store_root() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let real = tmp.path().join("real-store");
+    fs::create_dir_all(&real)?;
+    fs::write(real.join("site.gpg"), b"dummy")?;
+    let link = tmp.path().join("linked-store");
+    symlink(&real, &link)?;
+    let script = tmp.path().join("fake-pass");
+    fs::write(&script, "#!/bin/sh\nprintf '%s' \"$PASSWORD_STORE_DIR\"\n")?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "linked".into(),
+        Profile {
+            store: Some(link),
+            backend: Some(script.display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let app = App::new_with_profile(None, Some("linked"), config)?;
+    let dir = app.backend.show("site")?;
+    assert_eq!(dir, real.canonicalize()?.display().to_string());
+    Ok(())
+}
+
+#[test]
+fn doctor_reports_each_check() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join("site.gpg"), b"dummy")?;
+    let script = tmp.path().join("fake-pass");
+    fs::write(
+        &script,
+        "#!/bin/sh\necho 'gpg: decryption failed: No secret key' >&2\nexit 2\n",
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "fake".into(),
+        Profile {
+            store: Some(store.clone()),
+            backend: Some(script.display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let checks = App::doctor(None, Some("fake"), &config)?;
+    let outcome = |name: &str| {
+        checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| check.outcome.clone())
+            .expect("check ran")
+    };
+    assert!(outcome("pass on PATH").is_ok());
+    assert!(outcome("store directory").is_ok());
+    assert!(outcome(".gpg-id").is_err());
+    let decrypt = outcome("decrypt an entry").unwrap_err();
+    assert!(decrypt.starts_with("site: No secret key"), "{decrypt}");
+
+    let checks = App::doctor(Some(tmp.path().join("missing")), None, &config)?;
+    assert!(checks.iter().any(|check| check.outcome.is_err()));
+    Ok(())
+}
+
+#[test]
+fn plain_files_are_previewed_without_decrypting() -> anyhow::Result<()> {
+    use std::time::{Duration, Instant};
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join("README.txt"), "Shared logins live under team/\n")?;
+    fs::write(store.join("site.gpg"), b"dummy")?;
+
+    let config = Config {
+        index_plain: true,
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store), config)?;
+    app.apply_filter();
+    let row = app
+        .rows
+        .iter()
+        .position(|row| app.entries[row.idx].store_key() == "README.txt")
+        .expect("plain file listed");
+    app.cursor = row;
+    assert_eq!(app.selected_entry_path(), None);
+    app.enter();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.preview_loading.is_some() && Instant::now() < deadline {
+        app.poll_preview();
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(app.preview_text.trim(), "Shared logins live under team/");
+    assert!(!app.preview_masked);
+    Ok(())
+}
+
+#[test]
+fn marked_entries_are_deleted_together() -> anyhow::Result<()> {
+    use pass_tui::app::{Modal, PendingAction};
+    use pass_tui::config::Profile;
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(store.join("old"))?;
+    fs::write(store.join("old/forum.gpg"), b"dummy")?;
+    fs::write(store.join("blog.gpg"), b"dummy")?;
+    fs::write(store.join("keep.gpg"), b"dummy")?;
+    let log = tmp.path().join("log.txt");
+    let script = tmp.path().join("fake-pass");
+    fs::write(
+        &script,
+        format!("#!/bin/sh\necho \"$@\" >> {}\n", log.display()),
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "fake".into(),
+        Profile {
+            store: Some(store),
+            backend: Some(script.display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let mut app = App::new_with_profile(None, Some("fake"), config)?;
+    app.expanded.insert("old".into());
+    app.apply_filter();
+    for key in ["old", "old/forum", "blog"] {
+        app.cursor = app
+            .rows
+            .iter()
+            .position(|row| app.entries[row.idx].store_key() == key)
+            .expect("row listed");
+        app.toggle_selection();
+    }
+    // the entry inside the marked directory goes with it
+    assert_eq!(app.marked_keys(), vec!["blog", "old"]);
+
+    app.open_delete_modal();
+    assert!(matches!(app.modal, Some(Modal::Confirm { .. })));
+    if let Some(Modal::Confirm { selected_ok, .. }) = &mut app.modal {
+        *selected_ok = true;
+    }
+    let Some(PendingAction::DeleteMany(keys)) = app.submit_modal() else {
+        panic!("expected a bulk delete");
+    };
+    app.delete_many(&keys)?;
+    assert_eq!(fs::read_to_string(&log)?, "rm -f blog\nrm -r -f old\n");
+    assert!(app.selection.is_empty());
+    Ok(())
+}
+
+#[test]
+fn undo_moves_a_renamed_entry_back() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join("alpha.gpg"), b"dummy")?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "fallback".into(),
+        Profile {
+            store: Some(store.clone()),
+            // not installed, so mv falls back to renaming the file directly
+            backend: Some(tmp.path().join("no-such-pass").display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let mut app = App::new_with_profile(None, Some("fallback"), config.clone())?;
+    app.undo()?;
+    assert_eq!(
+        app.status.as_deref(),
+        Some("Nothing to undo (deletes cannot be undone)")
+    );
+
+    app.rename("alpha", "beta")?;
+    assert!(store.join("beta.gpg").is_file());
+
+    // the entry vanished behind our back, so the move back fails and can be retried
+    fs::rename(store.join("beta.gpg"), tmp.path().join("beta.gpg"))?;
+    assert!(app.undo().is_err());
+    assert_eq!(app.undo_stack.len(), 1);
+
+    fs::rename(tmp.path().join("beta.gpg"), store.join("beta.gpg"))?;
+    app.undo()?;
+    assert!(store.join("alpha.gpg").is_file() && !store.join("beta.gpg").exists());
+    assert_eq!(
+        app.status.as_deref(),
+        Some("Undid rename: 'beta' is back at 'alpha'")
+    );
+    assert!(app.undo_stack.is_empty());
+
+    config.dry_run = true;
+    let mut app = App::new_with_profile(None, Some("fallback"), config)?;
+    app.rename("alpha", "beta")?;
+    assert!(store.join("alpha.gpg").is_file());
+    assert!(
+        app.undo_stack.is_empty(),
+        "a dry run records nothing to undo"
+    );
+    Ok(())
+}
+
+#[test]
+fn cursor_stays_on_the_entry_across_refresh_and_rename() -> anyhow::Result<()> {
+    use pass_tui::config::Profile;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(store.join("archive"))?;
+    fs::write(store.join("alpha.gpg"), b"dummy")?;
+    fs::write(store.join("charlie.gpg"), b"dummy")?;
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "fallback".into(),
+        Profile {
+            store: Some(store.clone()),
+            // not installed, so mv falls back to renaming the file directly
+            backend: Some(tmp.path().join("no-such-pass").display().to_string()),
+            ..Profile::default()
+        },
+    );
+    let mut app = App::new_with_profile(None, Some("fallback"), config)?;
+    app.apply_filter();
+    assert!(app.select_key("charlie"));
+
+    fs::write(store.join("bravo.gpg"), b"dummy")?;
+    app.refresh()?;
+    assert_eq!(app.selected_store_key().as_deref(), Some("charlie"));
+
+    // the entry lands in a collapsed directory, which is expanded to keep it in view
+    app.rename("charlie", "archive")?;
+    app.refresh()?;
+    assert_eq!(app.selected_store_key().as_deref(), Some("archive/charlie"));
+    Ok(())
+}
+
+#[test]
+fn unwrapped_preview_pans_up_to_the_longest_line() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+
+    let mut app = App::new_with_store(Some(store), Config::default())?;
+    app.preview_text = "short\nhttps://example.com/a/very/long/path\n".to_string();
+    app.toggle_preview_wrap();
+    assert!(!app.preview_wrap);
+    app.pan_preview(8);
+    assert_eq!(app.preview_hscroll, 8);
+    app.pan_preview(1000);
+    assert_eq!(app.preview_hscroll, 35);
+    app.pan_preview(-1000);
+    assert_eq!(app.preview_hscroll, 0);
+
+    app.pan_preview(8);
+    app.toggle_preview_wrap();
+    assert_eq!(app.preview_hscroll, 0);
+    Ok(())
+}
+
+#[test]
+fn goto_row_is_one_based_and_clamped() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    for name in ["a", "b", "c"] {
+        fs::write(store.join(format!("{name}.gpg")), b"dummy")?;
+    }
+
+    let mut app = App::new_with_store(Some(store), Config::default())?;
+    app.apply_filter();
+    app.goto_row(2);
+    assert_eq!(app.selected_store_key().as_deref(), Some("b"));
+    app.goto_row(99);
+    assert_eq!(app.selected_store_key().as_deref(), Some("c"));
+    app.goto_row(0);
+    assert_eq!(app.selected_store_key().as_deref(), Some("a"));
+    Ok(())
+}
+
+#[test]
+fn filters_with_a_slash_match_the_full_path() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(store.join("work"))?;
+    fs::create_dir_all(store.join("home"))?;
+    fs::write(store.join("work/aws.gpg"), b"dummy")?;
+    fs::write(store.join("home/aws.gpg"), b"dummy")?;
+
+    let mut app = App::new_with_store(Some(store), Config::default())?;
+    app.filter = "work/aws".into();
+    app.apply_filter();
+    assert_eq!(row_keys(&app), ["work", "work/aws"]);
+
+    app.filter = "aws".into();
+    app.apply_filter();
+    assert_eq!(row_keys(&app), ["home", "home/aws", "work", "work/aws"]);
+    Ok(())
+}
+
+#[test]
+fn adding_from_an_empty_list_starts_at_the_current_directory() -> anyhow::Result<()> {
+    use pass_tui::app::{Modal, PendingAction};
+    use std::path::Path;
+
+    let tmp = TempDir::new()?;
+    fs::write(tmp.path().join(".gpg-id"), "me@example.com\n")?;
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    assert!(app.rows.is_empty());
+    app.open_add_modal();
+    match &mut app.modal {
+        Some(Modal::Input { buffer, .. }) => {
+            assert_eq!(buffer, "");
+            buffer.push_str("web/site");
+        }
+        other => panic!("expected the add modal, got {other:?}"),
+    }
+    match app.submit_modal() {
+        Some(PendingAction::Add(entry)) => assert_eq!(entry, "web/site"),
+        other => panic!("expected an add, got {other:?}"),
+    }
+
+    fs::create_dir_all(tmp.path().join("work/aws"))?;
+    fs::write(tmp.path().join("work/aws/prod.gpg"), b"dummy")?;
+    app.refresh()?;
+    app.change_dir(Path::new("work/aws"));
+    app.filter = "nothing-matches".into();
+    app.apply_filter();
+    assert!(app.rows.is_empty());
+    app.open_add_modal();
+    match &app.modal {
+        Some(Modal::Input { buffer, .. }) => assert_eq!(buffer, "work/aws/"),
+        other => panic!("expected the add modal, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn yank_reports_directories_and_failures() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(store.join("web"))?;
+    fs::write(store.join("web/site.gpg"), b"dummy")?;
+    let script = tmp.path().join("fake-pass");
+    fs::write(
+        &script,
+        "#!/bin/sh\necho 'Error: web/site is not in the password store.' >&2\nexit 1\n",
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let config = Config {
+        backend: Some(script.display().to_string()),
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store), config)?;
+    app.apply_filter();
+    app.yank_selected();
+    assert_eq!(app.status.as_deref(), Some("Select a file to copy"));
+
+    assert!(app.select_key("web/site"));
+    app.yank_selected();
+    let status = app.status.clone().unwrap_or_default();
+    assert!(
+        status.contains("web/site is not in the password store"),
+        "{status}"
+    );
+    assert!(app.clipboard_status().is_none());
+    Ok(())
+}
+
+#[test]
+fn copy_sequence_advances_one_field_per_press() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(store.join("web"))?;
+    fs::write(store.join("web/site.gpg"), b"dummy")?;
+    fs::write(store.join("other.gpg"), b"dummy")?;
+    let log = tmp.path().join("log");
+    let script = tmp.path().join("fake-pass");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}\nprintf 'hunter2\\nlogin: alice\\n'\n",
+            log.display()
+        ),
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let config = Config {
+        backend: Some(script.display().to_string()),
+        copy_sequence: vec!["url".into(), "password".into()],
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store), config)?;
+    app.apply_filter();
+    assert!(app.select_key("web/site"));
+    app.copy_next_in_sequence();
+    assert_eq!(
+        app.status.as_deref(),
+        Some("No url field in web/site — press again for password")
+    );
+    app.copy_next_in_sequence();
+    assert_eq!(
+        app.clipboard_status().unwrap_or_default(),
+        format!(
+            "Copied password (clears in {}s)",
+            pass_tui::clipboard::clip_time()
+        )
+    );
+    assert_eq!(fs::read_to_string(&log)?, "web/site\n-c web/site\n");
+    assert!(app.copy_step.is_none());
+
+    // another entry starts from the first field again
+    app.copy_next_in_sequence();
+    assert!(app.select_key("other"));
+    app.copy_next_in_sequence();
+    assert_eq!(app.copy_step, Some(("other".to_string(), 1)));
+    Ok(())
+}
+
+#[test]
+fn regenerating_copies_the_new_password_with_a_countdown() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join("site.gpg"), b"dummy")?;
+    let log = tmp.path().join("log");
+    let script = tmp.path().join("fake-pass");
+    fs::write(
+        &script,
+        format!("#!/bin/sh\necho \"$@\" >> {}\n", log.display()),
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let config = Config {
+        backend: Some(script.display().to_string()),
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store), config)?;
+    app.regenerate("site")?;
+    assert_eq!(fs::read_to_string(&log)?, "generate -i site 25\n-c site\n");
+    assert_eq!(
+        app.clipboard_status().unwrap_or_default(),
+        format!(
+            "Regenerated password for 'site' and copied it (clears in {}s)",
+            pass_tui::clipboard::clip_time()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn template_prefills_the_insert_box() -> anyhow::Result<()> {
+    use pass_tui::app::{Modal, PendingAction};
+
+    let tmp = TempDir::new()?;
+    let store = tmp.path().join("store");
+    fs::create_dir_all(&store)?;
+    fs::write(store.join(".gpg-id"), "me@example.com\n")?;
+    let template = tmp.path().join("template");
+    let config = Config {
+        template: Some(template.clone()),
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store), config)?;
+    app.open_template_modal();
+    assert!(app.modal.is_none());
+    assert!(app
+        .status
+        .as_deref()
+        .unwrap_or_default()
+        .contains("No entry template"));
+
+    fs::write(&template, "\nlogin: {{name}}\n")?;
+    app.open_template_modal();
+    if let Some(Modal::Input { buffer, .. }) = &mut app.modal {
+        buffer.push_str("mail/alice");
+    }
+    assert!(app.submit_modal().is_none());
+    match &mut app.modal {
+        Some(Modal::Multiline { buffer, .. }) => {
+            assert_eq!(buffer, "\nlogin: alice\n");
+            buffer.insert_str(0, "hunter2");
+        }
+        other => panic!("expected the contents box, got {other:?}"),
+    }
+    match app.submit_modal() {
+        Some(PendingAction::Insert { entry, content }) => {
+            assert_eq!(entry, "mail/alice");
+            assert_eq!(content, "hunter2\nlogin: alice\n");
+        }
+        other => panic!("expected an insert, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn flat_view_lists_entries_by_full_path() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("work/aws"))?;
+    fs::write(tmp.path().join("work/aws/prod.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("work/vpn.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("mail.gpg"), b"dummy")?;
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    assert_eq!(row_keys(&app), ["work", "mail"]);
+    app.cursor = 1;
+
+    app.toggle_flat();
+    assert_eq!(row_keys(&app), ["mail", "work/aws/prod", "work/vpn"]);
+    assert!(app.rows.iter().all(|row| row.branches.is_empty()));
+    assert_eq!(app.selected_entry_path().as_deref(), Some("mail"));
+
+    app.filter = "prod".into();
+    app.apply_filter();
+    assert_eq!(row_keys(&app), ["work/aws/prod"]);
+    assert_eq!(app.selected_entry_path().as_deref(), Some("work/aws/prod"));
+
+    app.toggle_flat();
+    assert_eq!(row_keys(&app), ["work", "work/aws", "work/aws/prod"]);
+    assert_eq!(app.selected_entry_path().as_deref(), Some("work/aws/prod"));
+    Ok(())
+}
+
+#[test]
+fn list_divider_moves_within_bounds() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    let config = Config {
+        list_width_percent: Some(95),
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), config)?;
+    assert_eq!(app.list_width, Some(80));
+    app.resize_list(5);
+    assert_eq!(app.list_width, Some(80));
+    app.resize_list(-5);
+    assert_eq!(app.list_width, Some(75));
+
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    assert_eq!(app.list_width, None);
+    for _ in 0..10 {
+        app.resize_list(-5);
+    }
+    assert_eq!(app.list_width, Some(20));
+    Ok(())
+}
+
+#[test]
+fn disk_paths_point_at_the_files() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    fs::create_dir_all(tmp.path().join("web"))?;
+    fs::write(tmp.path().join("web/site.gpg"), b"dummy")?;
+    fs::write(tmp.path().join("key.age"), b"dummy")?;
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    let root = app.store_dir.clone();
+    app.apply_filter();
+
+    app.select_key("web");
+    assert_eq!(app.selected_disk_path(), root.join("web"));
+    app.select_key("web/site");
+    assert_eq!(app.selected_disk_path(), root.join("web/site.gpg"));
+    app.select_key("key");
+    assert_eq!(app.selected_disk_path(), root.join("key.age"));
+
+    app.filter = "nothing-matches".into();
+    app.apply_filter();
+    assert_eq!(app.selected_disk_path(), root);
+    Ok(())
+}
+
+#[test]
+fn bulk_rename_previews_moves_and_skips_taken_names() -> anyhow::Result<()> {
+    use pass_tui::app::{Modal, PendingAction};
+
+    let tmp = TempDir::new()?;
+    for entry in ["oldco/a", "oldco/b", "oldco/x/c", "newco/b", "other/oldco"] {
+        let path = tmp.path().join(format!("{entry}.gpg"));
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, b"dummy")?;
+    }
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    let set_buffer = |app: &mut App, text: &str| {
+        if let Some(Modal::Input { buffer, .. }) = &mut app.modal {
+            *buffer = text.into();
+        }
+    };
+
+    app.open_bulk_rename_modal();
+    set_buffer(&mut app, "oldco/");
+    assert!(app.submit_modal().is_none());
+    set_buffer(&mut app, "newco/");
+    assert!(app.submit_modal().is_none());
+    match &mut app.modal {
+        Some(Modal::Confirm {
+            message,
+            selected_ok,
+            ..
+        }) => {
+            assert_eq!(
+                message,
+                "Rename 2 entries?\n  oldco/a -> newco/a\n  oldco/x/c -> newco/x/c\n\
+                 Skipping 1:\n  oldco/b: newco/b exists"
+            );
+            *selected_ok = true;
+        }
+        other => panic!("expected a confirmation, got {other:?}"),
+    }
+    match app.submit_modal() {
+        Some(PendingAction::MoveMany(moves)) => assert_eq!(moves.len(), 2),
+        other => panic!("expected the moves, got {other:?}"),
+    }
+
+    let (moves, skipped) = app.plan_bulk_rename("zzz", "y");
+    assert!(moves.is_empty() && skipped.is_empty());
+    Ok(())
+}
+
+#[test]
+fn the_list_can_be_driven_without_a_terminal() -> anyhow::Result<()> {
+    let tmp = TempDir::new()?;
+    for entry in ["mail/alice", "mail/bob", "web/site", "bank"] {
+        let path = tmp.path().join(format!("{entry}.gpg"));
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, b"dummy")?;
+    }
+    let mut app = App::new_with_store(Some(tmp.path().to_path_buf()), Config::default())?;
+    app.apply_filter();
+    assert_eq!(row_keys(&app), ["mail", "web", "bank"]);
+
+    assert!(app.toggle_expand());
+    assert_eq!(
+        row_keys(&app),
+        ["mail", "mail/alice", "mail/bob", "web", "bank"]
+    );
+    assert!(app.move_down());
+    assert!(!app.toggle_expand(), "entries do not expand");
+    assert!(app.move_up());
+    assert!(
+        !app.move_up(),
+        "the first row stays put without wrap_around"
+    );
+    assert!(app.toggle_expand());
+    assert_eq!(row_keys(&app), ["mail", "web", "bank"]);
+
+    app.set_filter("bob");
+    assert_eq!(row_keys(&app), ["mail", "mail/bob"]);
+    assert!(app.move_down());
+    assert_eq!(app.selected_entry_path().as_deref(), Some("mail/bob"));
+    assert!(!app.move_down());
+
+    app.config.wrap_around = true;
+    assert!(app.move_down());
+    assert_eq!(app.cursor, 0);
+    Ok(())
+}
@@ -3,20 +3,22 @@ use assert_fs::TempDir;
 use predicates::prelude::*;
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
+use std::sync::{Mutex, MutexGuard};
 
-#[test]
-fn pass_cli_backend_invokes_pass_commands() -> anyhow::Result<()> {
-    use pass_tui::backend::{Backend, PassCliBackend};
+// Tests share the process-wide PATH, so they take turns installing their fake pass
+static PATH_LOCK: Mutex<()> = Mutex::new(());
 
-    // Create a fake pass in PATH that logs arguments
-    let tmp = TempDir::new()?;
+/// Install a fake `pass` in PATH that logs its arguments and then runs `body`.
+fn fake_pass(tmp: &TempDir, body: &str) -> anyhow::Result<MutexGuard<'static, ()>> {
+    let guard = PATH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let bin_dir = tmp.child("bin");
     bin_dir.create_dir_all()?;
     let log = tmp.child("log.txt");
     let pass_path = bin_dir.child("pass");
     pass_path.write_str(&format!(
-        "#!/bin/sh\necho \"$@\" >> {}\nexit 0\n",
-        log.path().display()
+        "#!/bin/sh\necho \"$@\" >> {}\n{}\n",
+        log.path().display(),
+        body
     ))?;
     let mut perms = pass_path.metadata()?.permissions();
     perms.set_mode(0o755);
@@ -26,6 +28,16 @@ fn pass_cli_backend_invokes_pass_commands() -> anyhow::Result<()> {
     let orig_path = std::env::var("PATH").unwrap_or_default();
     let new_path = format!("{}:{}", bin_dir.path().display(), orig_path);
     std::env::set_var("PATH", &new_path);
+    Ok(guard)
+}
+
+#[test]
+fn pass_cli_backend_invokes_pass_commands() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let log = tmp.child("log.txt");
 
     let backend = PassCliBackend::default();
     backend.edit("foo/bar")?;
@@ -37,3 +49,734 @@ fn pass_cli_backend_invokes_pass_commands() -> anyhow::Result<()> {
     log.assert(predicate::str::contains("rm -f foo/bar"));
     Ok(())
 }
+
+#[test]
+fn generate_in_place_keeps_the_other_lines() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let backend = PassCliBackend::default();
+    backend.generate_in_place("foo/bar", 20, true)?;
+    backend.generate_in_place("foo/baz", 32, false)?;
+
+    tmp.child("log.txt")
+        .assert("generate -i -n foo/bar 20\ngenerate -i foo/baz 32\n");
+    Ok(())
+}
+
+#[test]
+fn replace_overwrites_with_every_line() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let stdin = tmp.child("stdin.txt");
+    let _path = fake_pass(&tmp, &format!("cat > {}", stdin.path().display()))?;
+    PassCliBackend::default().replace("foo/bar", "new\nlogin: alice")?;
+
+    tmp.child("log.txt").assert("insert -m -f foo/bar\n");
+    stdin.assert("new\nlogin: alice\n");
+    Ok(())
+}
+
+#[test]
+fn edit_failure_reports_missing_recipient_keys() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "echo 'gpg: 0xDEADBEEF: skipped: No public key' >&2\n\
+         echo 'gpg: [stdin]: encryption failed: No public key' >&2\n\
+         echo 'Password encryption aborted.' >&2\n\
+         exit 1",
+    )?;
+
+    let err = PassCliBackend::default().add("team/db").unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("0xDEADBEEF: skipped: No public key"),
+        "{message}"
+    );
+
+    let err = PassCliBackend::default()
+        .insert("team/db", "hunter2", false)
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("0xDEADBEEF: skipped: No public key"),
+        "{message}"
+    );
+    Ok(())
+}
+
+#[test]
+fn edit_exit_code_one_is_success_only_when_nothing_changed() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let path = fake_pass(&tmp, "echo 'Password unchanged.' >&2\nexit 1")?;
+    PassCliBackend::default().edit("web/site")?;
+
+    drop(path);
+    let _path = fake_pass(&tmp, "echo 'Password encryption aborted.' >&2\nexit 1")?;
+    let err = PassCliBackend::default().edit("web/site").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "pass edit failed: Password encryption aborted."
+    );
+    Ok(())
+}
+
+#[test]
+fn mv_runs_pass_mv_unless_destination_exists() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let log = tmp.child("log.txt");
+    let store = tmp.child("store");
+    store.child("a.gpg").write_str("dummy")?;
+    store.child("taken.gpg").write_str("dummy")?;
+
+    let backend = PassCliBackend::new(Some(store.path().to_path_buf()));
+    backend.mv("a", "b")?;
+    log.assert(predicate::str::contains("mv a b"));
+
+    let err = backend.mv("a", "taken").unwrap_err();
+    assert!(err.to_string().contains("destination exists"));
+    log.assert(predicate::str::contains("mv a taken").not());
+    Ok(())
+}
+
+#[test]
+fn otp_returns_code_or_pass_error() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "if [ \"$2\" = totp ]; then echo 123456; exit 0; fi\n\
+         echo 'No OTP key found for web/plain' >&2\n\
+         exit 1",
+    )?;
+
+    let backend = PassCliBackend::default();
+    assert_eq!(backend.otp("totp")?, "123456");
+    let err = backend.otp("web/plain").unwrap_err();
+    assert!(err.to_string().contains("No OTP key found"), "{err}");
+    tmp.child("log.txt")
+        .assert(predicate::str::contains("otp totp"));
+    Ok(())
+}
+
+#[test]
+fn git_captures_output_and_reports_failures() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "if [ \"$2\" = pull ]; then echo 'Already up to date.'; exit 0; fi\n\
+         echo 'fatal: no upstream configured' >&2\n\
+         exit 128",
+    )?;
+
+    let backend = PassCliBackend::default();
+    assert_eq!(backend.git(&["pull"])?.trim(), "Already up to date.");
+    let err = backend.git(&["push"]).unwrap_err();
+    assert!(err.to_string().contains("no upstream configured"), "{err}");
+    tmp.child("log.txt")
+        .assert(predicate::str::contains("git pull").and(predicate::str::contains("git push")));
+    Ok(())
+}
+
+#[test]
+fn insert_pipes_content_with_trailing_newline() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let stdin = tmp.child("stdin.txt");
+    let _path = fake_pass(&tmp, &format!("cat > {}", stdin.path().display()))?;
+
+    let backend = PassCliBackend::default();
+    backend.insert("web/site", "s3cret\nuser: bob", true)?;
+    tmp.child("log.txt")
+        .assert(predicate::str::contains("insert -m web/site"));
+    stdin.assert("s3cret\nuser: bob\n");
+    Ok(())
+}
+
+#[test]
+fn missing_program_is_reported_by_name() {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let backend = PassCliBackend {
+        program: Some("pass-tui-no-such-program".into()),
+        ..PassCliBackend::default()
+    };
+    let expected = "`pass-tui-no-such-program` not found on PATH";
+    assert_eq!(backend.check().unwrap_err().to_string(), expected);
+    assert_eq!(backend.show("web/site").unwrap_err().to_string(), expected);
+}
+
+#[test]
+fn mv_onto_a_directory_moves_into_it() -> anyhow::Result<()> {
+    use pass_tui::backend::{move_destination, Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let log = tmp.child("log.txt");
+    let store = tmp.child("store");
+    store.child("mail.gpg").write_str("dummy")?;
+    store.child("team/vpn.gpg").write_str("dummy")?;
+    store.child("old/vpn.gpg").write_str("dummy")?;
+
+    let backend = PassCliBackend::new(Some(store.path().to_path_buf()));
+    backend.mv("mail", "team/")?;
+    log.assert(predicate::str::contains("mv mail team/mail"));
+    backend.mv("mail", "a/b/c/mail")?;
+    log.assert(predicate::str::contains("mv mail a/b/c/mail"));
+
+    // team/vpn.gpg is taken, and an entry named like a directory would hide it
+    let err = backend.mv("old/vpn", "team").unwrap_err();
+    assert!(
+        err.to_string().contains("destination exists: team/vpn"),
+        "{err}"
+    );
+    let err = move_destination(store.path(), "team", "mail").unwrap_err();
+    assert!(
+        err.to_string().contains("destination exists: mail"),
+        "{err}"
+    );
+    assert!(move_destination(store.path(), "team", "team/sub").is_err());
+    Ok(())
+}
+
+#[test]
+fn show_failures_are_classified_from_stderr() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, GpgFailure, PassCliBackend, PassStatusError};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "if [ \"$1\" = locked ]; then echo 'gpg: public key decryption failed: Inappropriate ioctl for device' >&2; exit 2; fi\n\
+         echo 'gpg: decryption failed: No secret key' >&2\n\
+         exit 2",
+    )?;
+
+    let backend = PassCliBackend::default();
+    let err = backend.show("foreign").unwrap_err();
+    let status_err = err.downcast_ref::<PassStatusError>().expect("status error");
+    assert_eq!(status_err.failure, Some(GpgFailure::NoSecretKey));
+    assert!(!status_err.needs_unlock());
+    assert!(err.to_string().starts_with("No secret key"), "{err}");
+
+    let err = backend.show("locked").unwrap_err();
+    let status_err = err.downcast_ref::<PassStatusError>().expect("status error");
+    assert_eq!(status_err.failure, Some(GpgFailure::Locked));
+    assert!(status_err.needs_unlock());
+    Ok(())
+}
+
+#[test]
+fn failures_report_the_last_line_of_stderr() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "echo 'removing...' >&2\n\
+         echo 'rm: cannot remove: Permission denied' >&2\n\
+         echo >&2\n\
+         exit 1",
+    )?;
+
+    let backend = PassCliBackend::default();
+    let err = backend.rm("web/site", false).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "pass rm failed: rm: cannot remove: Permission denied"
+    );
+    let err = backend.yank("web/site").unwrap_err();
+    assert!(err.to_string().ends_with("Permission denied"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn cp_runs_pass_cp_unless_destination_exists() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let log = tmp.child("log.txt");
+    let store = tmp.child("store");
+    store.child("aws/old.gpg").write_str("dummy")?;
+    store.child("aws/taken.gpg").write_str("dummy")?;
+
+    let backend = PassCliBackend::new(Some(store.path().to_path_buf()));
+    backend.cp("aws/old", "aws/new")?;
+    backend.cp("aws", "aws-copy")?;
+    log.assert(
+        predicate::str::contains("cp aws/old aws/new")
+            .and(predicate::str::contains("cp aws aws-copy")),
+    );
+
+    let err = backend.cp("aws/old", "aws/taken").unwrap_err();
+    assert!(err.to_string().contains("destination exists"), "{err}");
+    let err = backend.cp("aws", "aws/nested").unwrap_err();
+    assert!(err.to_string().contains("inside itself"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn git_log_lists_commits_for_the_entry_file() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "printf 'abc1234 Rotate aws/prod\\n\\ndef5678 Add aws/prod\\n'",
+    )?;
+    let log = tmp.child("log.txt");
+    let store = tmp.child("store");
+    store.child("aws/prod.gpg").write_str("dummy")?;
+
+    let backend = PassCliBackend::new(Some(store.path().to_path_buf()));
+    let err = backend.git_log("aws/prod", 5).unwrap_err();
+    assert_eq!(err.to_string(), "not a git store");
+
+    store.child(".git").create_dir_all()?;
+    assert_eq!(
+        backend.git_log("aws/prod", 5)?,
+        vec!["abc1234 Rotate aws/prod", "def5678 Add aws/prod"]
+    );
+    log.assert(predicate::str::contains(
+        "git log --oneline -n 5 -- aws/prod.gpg",
+    ));
+
+    // passage stores keep entries in .age files, also once they are deleted
+    store.child("aws/dev.age").write_str("dummy")?;
+    let passage = PassCliBackend {
+        store_env: Some("PASSAGE_DIR".into()),
+        ..backend
+    };
+    passage.git_log("aws/dev", 5)?;
+    passage.git_log("aws/gone", 5)?;
+    log.assert(predicate::str::contains(
+        "git log --oneline -n 5 -- aws/dev.age",
+    ));
+    log.assert(predicate::str::contains(
+        "git log --oneline -n 5 -- aws/gone.age",
+    ));
+    Ok(())
+}
+
+#[test]
+fn passage_recipients_come_from_age_recipients() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+    use pass_tui::diagnostics;
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "echo hunter2")?;
+    let store = tmp.child("store");
+    store.child("web/site.age").write_str("dummy")?;
+    let backend = PassCliBackend {
+        store_dir: Some(store.path().to_path_buf()),
+        store_env: Some("PASSAGE_DIR".into()),
+        ..PassCliBackend::default()
+    };
+    let err = backend.recipients("web/site").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "No .age-recipients found for web/site; passage encrypts to its identities"
+    );
+    let checks = diagnostics::doctor(store.path(), &backend);
+    let check = checks.iter().find(|check| check.name == ".age-recipients");
+    assert!(check.is_some_and(|check| check.outcome.is_ok()));
+
+    store
+        .child("web/.age-recipients")
+        .write_str("# me\nage1qyqszqgpqyqszqgpqyqszqgpqyqszqgp\n")?;
+    assert_eq!(
+        backend.recipients("web/site")?,
+        vec!["age1qyqszqgpqyqszqgpqyqszqgpqyqszqgp"]
+    );
+    Ok(())
+}
+
+#[test]
+fn yank_pipes_the_first_line_to_the_chosen_clipboard_tool() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+    use pass_tui::clipboard::Clipboard;
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "printf 'hunter2\\nlogin: alice\\n'")?;
+    let log = tmp.child("log.txt");
+    let copied = tmp.child("copied.txt");
+    let xsel = tmp.child("bin/xsel");
+    xsel.write_str(&format!("#!/bin/sh\ncat > {}\n", copied.path().display()))?;
+    let mut perms = xsel.metadata()?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(xsel.path(), perms)?;
+
+    let backend = PassCliBackend {
+        clipboard: Clipboard::Xsel,
+        ..PassCliBackend::default()
+    };
+    backend.yank("web/site")?;
+    copied.assert("hunter2");
+    // decrypted with a plain `pass <entry>`, never `pass -c`
+    log.assert("web/site\n");
+
+    let backend = PassCliBackend {
+        clipboard: Clipboard::WlCopy,
+        ..PassCliBackend::default()
+    };
+    let err = backend.yank("web/site").unwrap_err();
+    assert_eq!(err.to_string(), "`wl-copy` not found on PATH");
+    Ok(())
+}
+
+#[test]
+fn yank_field_copies_only_that_fields_value() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+    use pass_tui::clipboard::Clipboard;
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "printf 'hunter2\\nlogin: alice\\nurl: example.com\\n'",
+    )?;
+    let copied = tmp.child("copied.txt");
+    let xsel = tmp.child("bin/xsel");
+    xsel.write_str(&format!("#!/bin/sh\ncat > {}\n", copied.path().display()))?;
+    let mut perms = xsel.metadata()?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(xsel.path(), perms)?;
+
+    let backend = PassCliBackend {
+        clipboard: Clipboard::Xsel,
+        ..PassCliBackend::default()
+    };
+    backend.yank_field("web/site", "login")?;
+    copied.assert("alice");
+
+    let err = backend.yank_field("web/site", "email").unwrap_err();
+    assert_eq!(err.to_string(), "No 'email' field in web/site");
+    copied.assert("alice");
+    Ok(())
+}
+
+#[test]
+fn copy_all_puts_every_line_of_the_entry_on_the_clipboard() -> anyhow::Result<()> {
+    use pass_tui::app::App;
+    use pass_tui::clipboard::Clipboard;
+    use pass_tui::config::Config;
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "printf 'hunter2\\nlogin: alice\\n'")?;
+    let copied = tmp.child("copied.txt");
+    let xsel = tmp.child("bin/xsel");
+    xsel.write_str(&format!("#!/bin/sh\ncat > {}\n", copied.path().display()))?;
+    let mut perms = xsel.metadata()?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(xsel.path(), perms)?;
+    let store = tmp.child("store");
+    store.child("web/site.gpg").write_str("dummy")?;
+    store
+        .child("web/site.gpg")
+        .assert(predicate::path::is_file());
+
+    let config = Config {
+        clipboard: Clipboard::Xsel,
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store.path().to_path_buf()), config)?;
+    app.yank_all_selected();
+    assert_eq!(app.status.as_deref(), Some("Select a file to copy"));
+
+    app.expanded.insert("web".into());
+    app.apply_filter();
+    assert!(app.select_key("web/site"));
+    app.yank_all_selected();
+    copied.assert("hunter2\nlogin: alice\n");
+    assert!(app
+        .clipboard_status()
+        .unwrap_or_default()
+        .starts_with("Copied all of web/site, every secret in it, to clipboard"));
+    Ok(())
+}
+
+#[test]
+fn copy_all_does_not_reuse_a_truncated_preview() -> anyhow::Result<()> {
+    use pass_tui::app::App;
+    use pass_tui::clipboard::Clipboard;
+    use pass_tui::config::Config;
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "printf 'hunter2\\n'\nhead -c 2048 /dev/zero | tr '\\0' n",
+    )?;
+    let log = tmp.child("log.txt");
+    let copied = tmp.child("copied.txt");
+    let xsel = tmp.child("bin/xsel");
+    xsel.write_str(&format!("#!/bin/sh\ncat > {}\n", copied.path().display()))?;
+    let mut perms = xsel.metadata()?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(xsel.path(), perms)?;
+    let store = tmp.child("store");
+    store.child("notes.gpg").write_str("dummy")?;
+
+    let config = Config {
+        clipboard: Clipboard::Xsel,
+        preview_max_kb: 1,
+        ..Config::default()
+    };
+    let mut app = App::new_with_store(Some(store.path().to_path_buf()), config)?;
+    app.apply_filter();
+    assert!(app.select_key("notes"));
+    app.update_preview();
+    while !app.poll_preview() {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(app.preview_text.contains("truncated"));
+
+    app.yank_all_selected();
+    let content = fs::read_to_string(copied.path())?;
+    assert_eq!(content.len(), "hunter2\n".len() + 2048);
+    assert!(!content.contains("truncated"));
+    // decrypted once for the preview and once more for the copy
+    log.assert("notes\nnotes\n");
+    Ok(())
+}
+
+#[test]
+fn unlock_decrypts_with_a_plain_show() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    PassCliBackend::default().unlock("web/site")?;
+    tmp.child("log.txt").assert("web/site\n");
+    Ok(())
+}
+
+#[test]
+fn passage_gets_its_store_from_passage_dir() -> anyhow::Result<()> {
+    use pass_tui::backend::{store_env_for, Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let passage = tmp.child("bin/passage");
+    passage
+        .write_str("#!/bin/sh\necho \"${PASSAGE_DIR:-unset} ${PASSWORD_STORE_DIR:-unset}\"\n")?;
+    let mut perms = passage.metadata()?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(passage.path(), perms)?;
+
+    let backend = PassCliBackend {
+        store_dir: Some("/stores/age".into()),
+        program: Some("passage".into()),
+        ..PassCliBackend::default()
+    };
+    assert_eq!(backend.show("web/site")?.trim(), "/stores/age unset");
+    assert_eq!(store_env_for("/usr/local/bin/passage"), "PASSAGE_DIR");
+    assert_eq!(store_env_for("gopass"), "PASSWORD_STORE_DIR");
+
+    let backend = PassCliBackend {
+        store_env: Some("MY_STORE".into()),
+        ..backend
+    };
+    assert_eq!(backend.show("web/site")?.trim(), "unset unset");
+    Ok(())
+}
+
+#[test]
+fn passage_moves_and_copies_age_entries() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let log = tmp.child("log.txt");
+    let passage = tmp.child("bin/passage");
+    passage.write_str(&format!(
+        "#!/bin/sh\necho \"passage $@\" >> {}\n",
+        log.path().display()
+    ))?;
+    let mut perms = passage.metadata()?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(passage.path(), perms)?;
+    let store = tmp.child("store");
+    store.child("web/site.age").write_str("x")?;
+    store.child("web/other.age").write_str("x")?;
+    store.child("web/taken.age").write_str("x")?;
+
+    let backend = PassCliBackend {
+        store_dir: Some(store.path().to_path_buf()),
+        program: Some("passage".into()),
+        ..PassCliBackend::default()
+    };
+    backend.mv("web/site", "web/renamed")?;
+    backend.cp("web/other", "web/copy")?;
+    assert!(backend.mv("web/other", "web/taken").is_err());
+    assert!(backend.mv("web/missing", "web/x").is_err());
+    log.assert(predicate::str::contains("passage mv web/site web/renamed"));
+    log.assert(predicate::str::contains("passage cp web/other web/copy"));
+    Ok(())
+}
+
+#[test]
+fn edit_refuses_to_start_without_an_editor() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "echo \"EDITOR=$EDITOR\" >> \"$(dirname \"$0\")/../log.txt\"",
+    )?;
+    let log = tmp.child("log.txt");
+
+    let backend = PassCliBackend {
+        editor: Some("no-such-editor --wait".into()),
+        ..PassCliBackend::default()
+    };
+    let err = backend.check_editor().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Editor `no-such-editor` from the profile not found"
+    );
+    assert!(backend.edit("web/site").is_err());
+    log.assert(predicate::path::missing());
+
+    let backend = PassCliBackend {
+        editor: Some("true --wait".into()),
+        ..PassCliBackend::default()
+    };
+    backend.edit("web/site")?;
+    log.assert(predicate::str::contains("EDITOR=true --wait"));
+    Ok(())
+}
+
+#[test]
+fn git_dirty_reads_porcelain_status() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "printf ' M web/site.gpg\\n'")?;
+    let log = tmp.child("log.txt");
+    let store = tmp.child("store");
+    store.create_dir_all()?;
+
+    let backend = PassCliBackend::new(Some(store.path().to_path_buf()));
+    assert!(!backend.git_dirty()?);
+    log.assert(predicate::path::missing());
+
+    store.child(".git").create_dir_all()?;
+    assert!(backend.git_dirty()?);
+    log.assert(predicate::str::contains("git status --porcelain"));
+    Ok(())
+}
+
+#[test]
+fn qr_encodes_the_otpauth_uri_or_the_password() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(
+        &tmp,
+        "case \"$1\" in\n  totp) printf 's3cret\\notpauth://totp/x?secret=ABC\\n' ;;\n  *) printf 'url: example.com\\npassword: hunter2\\n' ;;\nesac",
+    )?;
+    let log = tmp.child("log.txt");
+
+    let backend = PassCliBackend {
+        password_label: Some("password".into()),
+        ..PassCliBackend::default()
+    };
+    assert_eq!(
+        backend.secret_for_qr("totp")?,
+        "otpauth://totp/x?secret=ABC"
+    );
+    assert_eq!(backend.secret_for_qr("web/site")?, "hunter2");
+    // plain decryption only; no `pass show -q`, which needs extensions
+    log.assert("totp\nweb/site\n");
+    Ok(())
+}
+
+#[test]
+fn dry_run_leaves_the_store_alone() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "echo hunter2")?;
+    let log = tmp.child("log.txt");
+    let store = tmp.child("store");
+    store.child("web").create_dir_all()?;
+    store.child("web/site.gpg").write_str("x")?;
+
+    let backend = PassCliBackend {
+        store_dir: Some(store.path().to_path_buf()),
+        dry_run: true,
+        ..PassCliBackend::default()
+    };
+    backend.rm("web/site", false)?;
+    backend.mv("web/site", "web/renamed")?;
+    backend.cp("web/site", "web/copy")?;
+    backend.cp("web", "backup")?;
+    backend.insert("web/new", "s3cret", false)?;
+    backend.generate_in_place("web/site", 20, false)?;
+    // reading still runs pass
+    assert_eq!(backend.show("web/site")?, "hunter2\n");
+    log.assert("web/site\n");
+    store
+        .child("web/copy.gpg")
+        .assert(predicate::path::missing());
+    store.child("backup").assert(predicate::path::missing());
+    Ok(())
+}
+
+#[test]
+fn verbose_logs_each_command_once() -> anyhow::Result<()> {
+    use pass_tui::backend::{Backend, PassCliBackend};
+    use std::sync::{Arc, Mutex};
+
+    let tmp = TempDir::new()?;
+    let _path = fake_pass(&tmp, "exit 0")?;
+    let sink = Arc::new(Mutex::new(Vec::new()));
+    let backend = PassCliBackend {
+        store_dir: Some("/stores/main".into()),
+        verbose: true,
+        command_log: Some(sink.clone()),
+        ..PassCliBackend::default()
+    };
+    backend.rm("web/site", false)?;
+    backend.generate_in_place("web/site", 20, false)?;
+    backend.yank("web/site")?;
+
+    let logged = String::from_utf8(sink.lock().unwrap().clone())?;
+    let env = "PASSWORD_STORE_DIR=/stores/main";
+    assert_eq!(
+        logged,
+        format!(
+            "pass-tui: {env} pass rm -f web/site\n\
+             pass-tui: {env} pass generate -i web/site 20\n\
+             pass-tui: {env} pass -c web/site\n"
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn commands_are_described_as_a_shell_would_read_them() {
+    use pass_tui::backend::describe_command;
+    use std::process::Command;
+
+    let mut cmd = Command::new("pass");
+    cmd.env("PASSWORD_STORE_DIR", "/home/me/.password-store")
+        .args(["mv", "web/my site", "it's"]);
+    assert_eq!(
+        describe_command(&cmd),
+        "PASSWORD_STORE_DIR=/home/me/.password-store pass mv 'web/my site' 'it'\\''s'"
+    );
+}